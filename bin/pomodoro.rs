@@ -1,41 +1,74 @@
 // bin/pomodoro.rs
 use anyhow::Context;
-use clap::Parser;
+use clap::{Args, Parser, Subcommand};
+use std::io::{BufRead, IsTerminal, Write};
 use tracing::{info, warn};
 use tracing_subscriber::EnvFilter;
 
 // import depuis la lib (crate name from Cargo.toml: "pomodoro-cli" -> pomodoro_cli)
+use pomodoro_cli::domain::clock::Calendar;
+use pomodoro_cli::domain::layers;
+use pomodoro_cli::domain::stats::Stats;
+use pomodoro_cli::domain::status::DEFAULT_STATUS_FORMAT;
+use pomodoro_cli::infra::export::{ExportFormat, Output};
+use pomodoro_cli::infra::presets::PresetStore;
+use pomodoro_cli::infra::status_file::StatusFile;
+use pomodoro_cli::infra::storage::{
+    timestamp, JournalQuery, JsonlJournal, SessionEntry, SessionState,
+};
+use pomodoro_cli::ui::terminal::Terminal;
 use pomodoro_cli::{CliArgs, Config, Journal, SessionRunner};
 
 #[derive(Parser, Debug)]
 #[command(name = "pomodoro-cli", about = "CLI Pomodoro — offline, journal local")]
 struct Cli {
     /// Focus minutes (default 25)
-    #[arg(long)]
+    #[arg(long, global = true)]
     focus: Option<u64>,
 
     /// Short break minutes (default 5)
-    #[arg(long)]
+    #[arg(long, global = true)]
     short: Option<u64>,
 
     /// Long break minutes (default 15)
-    #[arg(long)]
+    #[arg(long, global = true)]
     long: Option<u64>,
 
     /// Cycles before long break (default 4)
-    #[arg(long)]
+    #[arg(long, global = true)]
     cycles: Option<u8>,
 
+    /// Long break after every N cycles, not only after the last one
+    #[arg(long, global = true)]
+    long_every: Option<u8>,
+
+    /// Repeat the cycles N times in one session (default 1)
+    #[arg(long, global = true, conflicts_with = "endless")]
+    rounds: Option<u8>,
+
+    /// Keep repeating the cycles until stopped
+    #[arg(long, default_value_t = false, global = true)]
+    endless: bool,
+
+    /// Flowtime: focus counts up until you end it (`s` + Enter), then a
+    /// break of focus/ratio
+    #[arg(long, default_value_t = false, global = true)]
+    flowtime: bool,
+
+    /// Flowtime break ratio (default 5: 50 min of flow earn a 10 min break)
+    #[arg(long, global = true)]
+    break_ratio: Option<u64>,
+
     /// Task label (<=80 chars)
-    #[arg(long)]
+    #[arg(long, global = true)]
     task: Option<String>,
 
     /// Play a beep on transitions
-    #[arg(long, default_value_t = false)]
+    #[arg(long, default_value_t = false, global = true)]
     beep: bool,
 
     /// Use desktop notifications (feature notify)
-    #[arg(long, default_value_t = false)]
+    #[arg(long, default_value_t = false, global = true)]
     notify: bool,
 
     /// Export today's journal to markdown
@@ -46,13 +79,230 @@ struct Cli {
     #[arg(long)]
     export_csv: bool,
 
-    /// Preset file (yaml/json) path
+    /// Export today's focus segments to an iCalendar file
     #[arg(long)]
+    export_ics: bool,
+
+    /// Preset file (yaml/json) path
+    #[arg(long, global = true)]
     preset: Option<std::path::PathBuf>,
 
+    /// Named preset from the preset library (see `preset list`)
+    #[arg(long, global = true, conflicts_with = "preset")]
+    preset_name: Option<String>,
+
     /// Launch the desktop GUI
     #[arg(long, default_value_t = false)]
     gui: bool,
+
+    /// Resume the latest unfinished session without asking
+    #[arg(long, default_value_t = false, conflicts_with = "fresh")]
+    resume: bool,
+
+    /// Ignore any unfinished session and start a new one
+    #[arg(long, default_value_t = false)]
+    fresh: bool,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Run the timer in the background, controlled over a Unix socket
+    Daemon,
+    /// Send a command to the running daemon (`start` uses --focus/--task/...)
+    Ctl {
+        #[command(subcommand)]
+        action: CtlAction,
+
+        /// Print the raw JSON response
+        #[arg(long, default_value_t = false)]
+        json: bool,
+    },
+    /// Show the effective configuration and where each value comes from
+    Config {
+        /// Print as JSON
+        #[arg(long, default_value_t = false)]
+        json: bool,
+    },
+    /// Manage named presets (built-in and saved in the config dir)
+    Preset {
+        #[command(subcommand)]
+        action: PresetAction,
+    },
+    /// List journaled sessions of all days
+    Log {
+        #[command(flatten)]
+        filter: JournalFilter,
+
+        /// Print the sessions as JSON
+        #[arg(long, default_value_t = false)]
+        json: bool,
+    },
+    /// Focus time per day/week/month and task, outcomes and streaks
+    Stats {
+        #[command(flatten)]
+        filter: JournalFilter,
+
+        /// Print the statistics as JSON
+        #[arg(long, default_value_t = false)]
+        json: bool,
+    },
+    /// Export journaled sessions (today unless a range is given)
+    Export {
+        /// md, csv (one row per session), segments-csv (one row per segment),
+        /// ics (one calendar event per focus segment), sessions-ics (per session)
+        /// or html (report with charts)
+        format: ExportFormat,
+
+        #[command(flatten)]
+        filter: JournalFilter,
+
+        /// File to write, `-` for stdout (default)
+        #[arg(short, long, default_value = "-")]
+        output: Output,
+    },
+    /// Rewrite past journal files to one line per session
+    Compact,
+    /// Copy the JSONL journal history into the SQLite journal (needs the
+    /// `sqlite` feature); later sessions are journaled in SQLite
+    Import,
+    /// Print the current timer status (for shell prompts and status bars)
+    Status {
+        /// Format string: {kind} {remaining} {remaining_secs} {elapsed} {percent}
+        /// {cycle} {cycles} {segment} {segments} {task} {state}
+        #[arg(long, default_value = DEFAULT_STATUS_FORMAT)]
+        format: String,
+
+        /// Text printed when no session is running
+        #[arg(long, default_value = "")]
+        idle: String,
+
+        /// Print JSON for a waybar `custom` module (return-type json)
+        #[arg(long, default_value_t = false, conflicts_with = "json")]
+        waybar: bool,
+
+        /// Print the full status as JSON
+        #[arg(long, default_value_t = false)]
+        json: bool,
+    },
+}
+
+/// Which journaled sessions `log`, `stats` and `export` look at.
+#[derive(Args, Debug)]
+struct JournalFilter {
+    /// First day (YYYY-MM-DD), inclusive
+    #[arg(long, value_parser = parse_date, conflicts_with_all = ["week", "month"])]
+    from: Option<time::Date>,
+
+    /// Last day (YYYY-MM-DD), inclusive
+    #[arg(long, value_parser = parse_date, conflicts_with_all = ["week", "month"])]
+    to: Option<time::Date>,
+
+    /// An ISO week (YYYY-Www), the current one without a value
+    #[arg(long, value_name = "YYYY-Www", value_parser = parse_week, conflicts_with = "month")]
+    week: Option<Option<time::Date>>,
+
+    /// A month (YYYY-MM), the current one without a value
+    #[arg(long, value_name = "YYYY-MM", value_parser = parse_month)]
+    month: Option<Option<time::Date>>,
+
+    /// Only sessions whose task contains this text (case-insensitive)
+    #[arg(long)]
+    task_contains: Option<String>,
+
+    /// Only sessions tagged #TAG in their task
+    #[arg(long)]
+    tag: Option<String>,
+
+    /// Only sessions in this state: ongoing, completed, interrupted
+    #[arg(long)]
+    state: Option<SessionState>,
+}
+
+impl JournalFilter {
+    fn has_range(&self) -> bool {
+        self.from.is_some() || self.to.is_some() || self.week.is_some() || self.month.is_some()
+    }
+
+    /// `--week`/`--month` become a day range, relative to `today`.
+    fn query(&self, today: time::Date) -> JournalQuery {
+        let (from, to) = if let Some(week) = self.week {
+            let monday = week.unwrap_or_else(|| {
+                today - time::Duration::days(today.weekday().number_days_from_monday().into())
+            });
+            (Some(monday), Some(monday + time::Duration::days(6)))
+        } else if let Some(month) = self.month {
+            let first = month.unwrap_or_else(|| today.replace_day(1).unwrap_or(today));
+            // 31 days after the 1st is always in the next month
+            let next = (first + time::Duration::days(31))
+                .replace_day(1)
+                .unwrap_or(first);
+            let last = next - time::Duration::days(1);
+            (Some(first), Some(last))
+        } else {
+            (self.from, self.to)
+        };
+        JournalQuery {
+            from,
+            to,
+            task: self.task_contains.clone(),
+            tag: self.tag.clone(),
+            state: self.state.clone(),
+        }
+    }
+}
+
+#[derive(Subcommand, Debug)]
+enum PresetAction {
+    /// List built-in and saved presets
+    List,
+    /// Print a preset's settings
+    Show { name: String },
+    /// Save the effective timer and schedule (files, env, flags) under NAME
+    Save {
+        name: String,
+
+        /// Replace an existing preset
+        #[arg(long, default_value_t = false)]
+        force: bool,
+    },
+    /// Delete a saved preset
+    Delete { name: String },
+}
+
+#[derive(Subcommand, Debug, Clone, Copy)]
+enum CtlAction {
+    Start,
+    Pause,
+    Resume,
+    Skip,
+    Stop,
+    Status,
+}
+
+impl Cli {
+    /// Map clap's `Cli` into the library-level `CliArgs` DTO.
+    fn lib_args(&self) -> CliArgs {
+        CliArgs {
+            focus: self.focus,
+            short: self.short,
+            long: self.long,
+            cycles: self.cycles,
+            long_every: self.long_every,
+            rounds: self.rounds,
+            endless: self.endless,
+            flowtime: self.flowtime,
+            break_ratio: self.break_ratio,
+            task: self.task.clone(),
+            preset: self.preset.clone(),
+            preset_name: self.preset_name.clone(),
+            gui: self.gui,
+            beep: self.beep,
+            notify: self.notify,
+        }
+    }
 }
 
 fn main() -> anyhow::Result<()> {
@@ -60,6 +310,8 @@ fn main() -> anyhow::Result<()> {
     tracing_subscriber::fmt()
         .with_env_filter(EnvFilter::from_default_env())
         .init();
+    // before any thread starts, see `init_local_offset`
+    pomodoro_cli::domain::clock::init_local_offset();
 
     let cli = Cli::parse();
     info!("Starting pomodoro");
@@ -67,53 +319,430 @@ fn main() -> anyhow::Result<()> {
     // If we are launching the GUI, avoid creating a Tokio runtime here.
     // Dioxus desktop initializes its own runtime, and nesting runtimes panics.
     if cli.gui {
-        let lib_cli = CliArgs {
-            focus: cli.focus,
-            short: cli.short,
-            long: cli.long,
-            cycles: cli.cycles,
-            task: cli.task.clone(),
-            preset: cli.preset.clone(),
-            gui: cli.gui,
-        };
-        pomodoro_cli::ui::gui::run_gui(lib_cli);
+        pomodoro_cli::ui::gui::run_gui(cli.lib_args());
         return Ok(());
     }
 
+    if let Some(Command::Ctl { action, json }) = &cli.command {
+        return run_ctl(&cli, *action, *json);
+    }
+    if let Some(Command::Config { json }) = &cli.command {
+        return show_config(&cli, *json);
+    }
+    if let Some(Command::Preset { action }) = &cli.command {
+        return run_preset(&cli, action);
+    }
+    if let Some(Command::Log { filter, json }) = &cli.command {
+        let calendar = calendar(&cli)?;
+        return run_log(&calendar, &filter.query(today(&calendar)), *json);
+    }
+    if let Some(Command::Stats { filter, json }) = &cli.command {
+        let calendar = calendar(&cli)?;
+        return run_stats(&calendar, &filter.query(today(&calendar)), *json);
+    }
+    if let Some(Command::Export {
+        format,
+        filter,
+        output,
+    }) = &cli.command
+    {
+        let journal = Journal::open_default(calendar(&cli)?)?;
+        let today = journal.today();
+        // without a range, today's sessions as the --export-* flags do
+        let mut query = filter.query(today);
+        if !filter.has_range() {
+            (query.from, query.to) = (Some(today), Some(today));
+        }
+        let n = journal.export(*format, &query, output)?;
+        if let Output::File(path) = output {
+            println!("exported {} sessions to {}", n, path.display());
+        }
+        return Ok(());
+    }
+    if let Some(Command::Import) = &cli.command {
+        return run_import(&calendar(&cli)?);
+    }
+    if let Some(Command::Compact) = &cli.command {
+        let calendar = calendar(&cli)?;
+        let today = today(&calendar);
+        let report = JsonlJournal::open_default(today)?.compact(today)?;
+        println!(
+            "compacted {} files: {} lines -> {} lines",
+            report.files, report.lines_before, report.lines_after
+        );
+        if report.unparsed > 0 {
+            println!(
+                "kept {} unreadable lines as they were (see the warnings)",
+                report.unparsed
+            );
+        }
+        return Ok(());
+    }
+    if let Some(Command::Status {
+        format,
+        idle,
+        waybar,
+        json,
+    }) = &cli.command
+    {
+        return run_status(format, idle, *waybar, *json);
+    }
+
     let rt = tokio::runtime::Builder::new_multi_thread()
         .enable_all()
         .build()
         .context("Failed to build Tokio runtime")?;
 
+    if let Some(Command::Daemon) = cli.command {
+        return rt.block_on(run_daemon(cli));
+    }
     rt.block_on(run_cli(cli))
 }
 
+/// `config`: the files that were looked for, then one line per setting.
+fn show_config(cli: &Cli, json: bool) -> anyhow::Result<()> {
+    let resolved = layers::load(&cli.lib_args())?;
+    let rows = resolved.describe();
+    if json {
+        let out: serde_json::Map<String, serde_json::Value> = rows
+            .into_iter()
+            .map(|(key, value, source)| {
+                let entry = serde_json::json!({ "value": value, "source": source.to_string() });
+                (key, entry)
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&out)?);
+        return Ok(());
+    }
+
+    let cwd = std::env::current_dir()?;
+    let found = |p: Option<std::path::PathBuf>| match p {
+        Some(p) if p.is_file() => p.display().to_string(),
+        Some(p) => format!("{} (not found)", p.display()),
+        None => "(none)".to_string(),
+    };
+    println!("# user file:    {}", found(layers::user_config_path()));
+    println!("# project file: {}", found(layers::find_project_file(&cwd)));
+    let width = rows.iter().map(|(k, _, _)| k.len()).max().unwrap_or(0);
+    for (key, value, source) in rows {
+        let value = match value {
+            serde_json::Value::Null => "(none)".to_string(),
+            v => v.to_string(),
+        };
+        println!("{:width$} = {:20} # {}", key, value, source, width = width);
+    }
+    Ok(())
+}
+
+fn run_preset(cli: &Cli, action: &PresetAction) -> anyhow::Result<()> {
+    let store = PresetStore::open_default()?;
+    match action {
+        PresetAction::List => {
+            let presets = store.list()?;
+            let width = presets.iter().map(|p| p.name.len()).max().unwrap_or(0);
+            for p in presets {
+                let origin = if p.builtin { "built-in" } else { "saved" };
+                println!(
+                    "{:width$}  {:8}  {}",
+                    p.name,
+                    origin,
+                    p.description,
+                    width = width
+                );
+            }
+        }
+        PresetAction::Show { name } => {
+            let cfg = store.load(name)?;
+            print!("{}", toml::to_string(&cfg)?);
+        }
+        PresetAction::Save { name, force } => {
+            let cfg = layers::load(&cli.lib_args())?.config;
+            let path = store.save(name, &cfg, *force)?;
+            println!("saved {} to {}", name, path.display());
+        }
+        PresetAction::Delete { name } => {
+            let path = store.delete(name)?;
+            println!("deleted {}", path.display());
+        }
+    }
+    Ok(())
+}
+
+fn parse_date(s: &str) -> Result<time::Date, String> {
+    use time::format_description::well_known::Iso8601;
+    time::Date::parse(s, &Iso8601::DATE).map_err(|e| format!("expected YYYY-MM-DD: {}", e))
+}
+
+fn parse_week(s: &str) -> Result<time::Date, String> {
+    let err = || format!("expected YYYY-Www, e.g. 2024-W18: {:?}", s);
+    let (year, week) = s.split_once("-W").ok_or_else(err)?;
+    let year = year.parse().map_err(|_| err())?;
+    let week = week.parse().map_err(|_| err())?;
+    time::Date::from_iso_week_date(year, week, time::Weekday::Monday).map_err(|e| e.to_string())
+}
+
+fn parse_month(s: &str) -> Result<time::Date, String> {
+    let err = || format!("expected YYYY-MM, e.g. 2024-05: {:?}", s);
+    let (year, month) = s.split_once('-').ok_or_else(err)?;
+    let year = year.parse().map_err(|_| err())?;
+    let month: u8 = month.parse().map_err(|_| err())?;
+    let month = time::Month::try_from(month).map_err(|e| e.to_string())?;
+    time::Date::from_calendar_date(year, month, 1).map_err(|e| e.to_string())
+}
+
+/// Journal days of the configuration (`day_start_hour`, local time zone).
+fn calendar(cli: &Cli) -> anyhow::Result<Calendar> {
+    Ok(layers::load(&cli.lib_args())?.config.calendar())
+}
+
+fn today(calendar: &Calendar) -> time::Date {
+    calendar.day_of(time::OffsetDateTime::now_utc())
+}
+
+/// `log`: one line per session, oldest first, in local time.
+fn run_log(calendar: &Calendar, query: &JournalQuery, json: bool) -> anyhow::Result<()> {
+    let entries = Journal::open_default(*calendar)?.query(query)?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&entries)?);
+        return Ok(());
+    }
+    if entries.is_empty() {
+        println!("no sessions");
+        return Ok(());
+    }
+    println!(
+        "{:16}  {:11}  {:>7}  {:18}  TASK",
+        "START", "STATE", "FOCUS", "PROGRESS"
+    );
+    for e in &entries {
+        let local = calendar.local_time(e.start);
+        let start = format!("{} {:02}:{:02}", local.date(), local.hour(), local.minute());
+        println!(
+            "{:16}  {:11}  {:>7}  {:18}  {}",
+            start,
+            format!("{:?}", e.state),
+            hours_minutes(e.focus_secs()),
+            e.progress(),
+            e.cfg.task.as_deref().unwrap_or("-")
+        );
+    }
+    Ok(())
+}
+
+#[cfg(feature = "sqlite")]
+fn run_import(calendar: &Calendar) -> anyhow::Result<()> {
+    use pomodoro_cli::infra::sqlite::{SqliteJournal, DB_FILE};
+
+    let jsonl = JsonlJournal::open_default(today(calendar))?;
+    let db = jsonl.dir.join(DB_FILE);
+    let backend = SqliteJournal::open(&db)?;
+    let journal = Journal::with_backend(jsonl.dir.clone(), std::sync::Arc::new(backend));
+    let n = journal.import(&jsonl)?;
+    println!(
+        "imported {} sessions into {} (the JSONL files are kept)",
+        n,
+        db.display()
+    );
+    Ok(())
+}
+
+#[cfg(not(feature = "sqlite"))]
+fn run_import(_calendar: &Calendar) -> anyhow::Result<()> {
+    anyhow::bail!("importing into SQLite needs a build with `--features sqlite`")
+}
+
+/// `stats`: totals, then focus time per period and per task.
+fn run_stats(calendar: &Calendar, query: &JournalQuery, json: bool) -> anyhow::Result<()> {
+    let entries = Journal::open_default(*calendar)?.query(query)?;
+    let today = today(calendar);
+    let stats = Stats::compute(&entries, calendar, today);
+    if json {
+        println!("{}", serde_json::to_string_pretty(&stats)?);
+        return Ok(());
+    }
+    println!(
+        "Sessions  {} ({} completed, {} interrupted, {} ongoing{})",
+        stats.sessions,
+        stats.completed,
+        stats.interrupted,
+        stats.ongoing,
+        stats
+            .completion_rate()
+            .map(|r| format!(", {}% completed", r))
+            .unwrap_or_default()
+    );
+    println!(
+        "Focus     {} (sessions run {} on average)",
+        hours_minutes(stats.focus_secs),
+        hours_minutes(stats.avg_session_secs)
+    );
+    println!(
+        "Streak    {} days (longest {})",
+        stats.current_streak_days, stats.longest_streak_days
+    );
+    for (title, rows) in [
+        ("Per day", &stats.per_day),
+        ("Per week", &stats.per_week),
+        ("Per month", &stats.per_month),
+    ] {
+        println!("\n{}", title);
+        for r in rows {
+            println!(
+                "  {:10}  {:>7}  {} sessions",
+                r.period,
+                hours_minutes(r.focus_secs),
+                r.sessions
+            );
+        }
+    }
+    println!("\nPer task");
+    for t in &stats.per_task {
+        println!(
+            "  {:>7}  {:3} sessions  {}",
+            hours_minutes(t.focus_secs),
+            t.sessions,
+            t.task.as_deref().unwrap_or("(no task)")
+        );
+    }
+    println!("\n{}", calendar.describe());
+    Ok(())
+}
+
+/// `1h05m`
+fn hours_minutes(secs: u64) -> String {
+    format!("{}h{:02}m", secs / 3600, secs % 3600 / 60)
+}
+
+fn run_status(format: &str, idle: &str, waybar: bool, json: bool) -> anyhow::Result<()> {
+    let status = StatusFile::open_default()?.read(time::OffsetDateTime::now_utc())?;
+    if waybar {
+        println!("{}", serde_json::to_string(&status.waybar(format))?);
+    } else if json {
+        println!("{}", serde_json::to_string(&status)?);
+    } else if status.is_active() {
+        println!("{}", status.render(format));
+    } else {
+        println!("{}", idle);
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+async fn run_daemon(cli: Cli) -> anyhow::Result<()> {
+    use pomodoro_cli::infra::daemon::{socket_path, Daemon};
+
+    let (tx, mut rx) = tokio::sync::watch::channel(false);
+    ctrlc::set_handler(move || {
+        tx.send_replace(true);
+    })
+    .context("installing ctrlc handler")?;
+    let shutdown = async move {
+        let _ = rx.wait_for(|stop| *stop).await;
+    };
+    Daemon::new(socket_path()?, cli.beep, cli.notify)
+        .serve(shutdown)
+        .await
+}
+
+#[cfg(not(unix))]
+async fn run_daemon(_cli: Cli) -> anyhow::Result<()> {
+    anyhow::bail!("daemon mode needs Unix domain sockets")
+}
+
+#[cfg(unix)]
+fn run_ctl(cli: &Cli, action: CtlAction, json: bool) -> anyhow::Result<()> {
+    use pomodoro_cli::infra::daemon::{send, socket_path, Request};
+
+    let req = match action {
+        CtlAction::Start => Request::Start {
+            focus: cli.focus,
+            short: cli.short,
+            long: cli.long,
+            cycles: cli.cycles,
+            long_every: cli.long_every,
+            rounds: cli.rounds,
+            endless: cli.endless,
+            flowtime: cli.flowtime,
+            break_ratio: cli.break_ratio,
+            task: cli.task.clone(),
+            // the daemon runs in its own working directory
+            preset: cli
+                .preset
+                .as_deref()
+                .map(|p| {
+                    std::fs::canonicalize(p)
+                        .with_context(|| format!("finding preset {}", p.display()))
+                })
+                .transpose()?,
+            preset_name: cli.preset_name.clone(),
+        },
+        CtlAction::Pause => Request::Pause,
+        CtlAction::Resume => Request::Resume,
+        CtlAction::Skip => Request::Skip,
+        CtlAction::Stop => Request::Stop,
+        CtlAction::Status => Request::Status,
+    };
+    let resp = send(&socket_path()?, &req)?;
+    if json {
+        println!("{}", serde_json::to_string(&resp)?);
+    } else {
+        println!("{}", resp.status.summary());
+    }
+    match resp.message {
+        Some(msg) if !resp.ok => anyhow::bail!(msg),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(not(unix))]
+fn run_ctl(_cli: &Cli, _action: CtlAction, _json: bool) -> anyhow::Result<()> {
+    anyhow::bail!("daemon mode needs Unix domain sockets")
+}
+
 async fn run_cli(cli: Cli) -> anyhow::Result<()> {
     // --- map clap's `Cli` into the library-level `CliArgs` DTO
-    let lib_cli = CliArgs {
-        focus: cli.focus,
-        short: cli.short,
-        long: cli.long,
-        cycles: cli.cycles,
-        task: cli.task.clone(),
-        preset: cli.preset.clone(),
-        gui: cli.gui,
-    };
+    let lib_cli = cli.lib_args();
 
     // call lib API with the mapped type
     let cfg = Config::from_cli_and_preset(&lib_cli)
         .context("Failed to build configuration from CLI/preset")?;
 
-    let journal = Journal::open_default().context("opening journal")?;
+    let calendar = cfg.calendar();
+    let journal = Journal::open_default(calendar).context("opening journal")?;
+    let unfinished = if cli.fresh {
+        None
+    } else {
+        journal.latest_unfinished().unwrap_or_else(|e| {
+            warn!("Could not look for an unfinished session: {:?}", e);
+            None
+        })
+    };
     let mut runner = SessionRunner::new(cfg, journal, cli.beep, cli.notify);
+    match StatusFile::open_default() {
+        Ok(f) => runner = runner.with_status_file(f),
+        Err(e) => warn!("status file disabled: {:#}", e),
+    }
+
+    if let Some(entry) = unfinished {
+        if cli.resume || ask_resume(&entry, &calendar)? {
+            runner.resume_from(entry);
+        }
+    }
 
     // ctrlc handling: ensure save on interrupt
     runner.install_ctrlc_handler()?;
 
+    let terminal = Terminal::new(cli.task.clone())?;
+    terminal.listen_for_pause(runner.control());
+    let ui = tokio::spawn(terminal.follow(runner.subscribe()));
+
     let result = runner.run().await;
+    let _ = ui.await;
 
+    let mut interrupted = false;
     match result {
         Ok(meta) => {
+            interrupted = meta.state == SessionState::Interrupted;
             info!("Session finished: {:?}", meta);
         }
         Err(e) => {
@@ -128,6 +757,32 @@ async fn run_cli(cli: Cli) -> anyhow::Result<()> {
     if cli.export_csv {
         runner.export_csv().context("export csv")?;
     }
+    if cli.export_ics {
+        runner.export_ics().context("export ics")?;
+    }
 
+    if interrupted {
+        std::process::exit(130);
+    }
     Ok(())
 }
+
+/// Ask on the terminal whether to resume `entry`. Defaults to "yes"; never
+/// resumes when stdin is not a tty (scripts must pass `--resume`).
+fn ask_resume(entry: &SessionEntry, calendar: &Calendar) -> anyhow::Result<bool> {
+    let stdin = std::io::stdin();
+    if !stdin.is_terminal() {
+        return Ok(false);
+    }
+    print!(
+        "Unfinished session from {} ({}, {}). Resume? [Y/n] ",
+        timestamp(calendar.local_time(entry.start)),
+        entry.cfg.task.as_deref().unwrap_or("no task"),
+        entry.progress()
+    );
+    std::io::stdout().flush()?;
+    let mut answer = String::new();
+    stdin.lock().read_line(&mut answer)?;
+    let answer = answer.trim().to_lowercase();
+    Ok(answer.is_empty() || answer == "y" || answer == "yes")
+}
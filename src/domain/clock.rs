@@ -0,0 +1,197 @@
+use futures_util::future::BoxFuture;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use time::{Date, OffsetDateTime, PrimitiveDateTime, Time, UtcOffset};
+use tracing::warn;
+
+/// Time source used by the session engine, the UIs and the journal.
+/// `SystemClock` in production, `VirtualClock` for deterministic tests.
+///
+/// Segment timing is driven by `monotonic`; `now` is only used for
+/// timestamps. Comparing the two is how suspends and clock jumps are detected.
+pub trait Clock: Send + Sync {
+    /// Current wall-clock time. Journal timestamps keep its offset.
+    fn now(&self) -> OffsetDateTime;
+
+    /// Monotonic time since an arbitrary origin. Never goes back, and (like
+    /// `CLOCK_MONOTONIC`) does not advance while the machine is suspended.
+    fn monotonic(&self) -> Duration;
+
+    /// Wait for `d` of monotonic time.
+    fn sleep(&self, d: Duration) -> BoxFuture<'static, ()>;
+}
+
+pub type SharedClock = Arc<dyn Clock>;
+
+/// Real time in the local offset (see `init_local_offset`), `Instant` +
+/// `tokio::time::sleep`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> OffsetDateTime {
+        let offset = LOCAL_OFFSET.get().copied().unwrap_or(UtcOffset::UTC);
+        OffsetDateTime::now_utc().to_offset(offset)
+    }
+
+    fn monotonic(&self) -> Duration {
+        static ORIGIN: OnceLock<Instant> = OnceLock::new();
+        ORIGIN.get_or_init(Instant::now).elapsed()
+    }
+
+    fn sleep(&self, d: Duration) -> BoxFuture<'static, ()> {
+        Box::pin(tokio::time::sleep(d))
+    }
+}
+
+static LOCAL_OFFSET: OnceLock<UtcOffset> = OnceLock::new();
+
+/// The local UTC offset, read once. `time` cannot read it once the process
+/// runs several threads, so `main` calls this first; UTC when unknown.
+pub fn init_local_offset() -> UtcOffset {
+    *LOCAL_OFFSET.get_or_init(|| {
+        UtcOffset::current_local_offset().unwrap_or_else(|_| {
+            warn!("could not determine the local time zone, using UTC");
+            UtcOffset::UTC
+        })
+    })
+}
+
+/// How instants map to journal days: a UTC offset and the hour a day starts
+/// at (with 4, a session at 01:30 belongs to the day before). Times keep the
+/// offset they were recorded in, so sessions on both sides of a DST change
+/// land on their own local day; times in UTC (journals written before they
+/// carried the offset) are taken in `offset`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Calendar {
+    pub offset: UtcOffset,
+    pub day_start_hour: u8,
+}
+
+impl Default for Calendar {
+    /// UTC, days starting at midnight.
+    fn default() -> Self {
+        Self {
+            offset: UtcOffset::UTC,
+            day_start_hour: 0,
+        }
+    }
+}
+
+impl Calendar {
+    /// The local time zone, days starting at `day_start_hour`.
+    pub fn local(day_start_hour: u8) -> Self {
+        Self {
+            offset: init_local_offset(),
+            day_start_hour,
+        }
+    }
+
+    /// `t` in the offset it was recorded in, or in this calendar's offset
+    /// when it is in UTC.
+    pub fn local_time(&self, t: OffsetDateTime) -> OffsetDateTime {
+        if t.offset().is_utc() {
+            t.to_offset(self.offset)
+        } else {
+            t
+        }
+    }
+
+    /// Journal day `t` belongs to.
+    pub fn day_of(&self, t: OffsetDateTime) -> Date {
+        (self.local_time(t) - time::Duration::hours(self.day_start_hour.into())).date()
+    }
+
+    /// First instant of journal day `day`, in this calendar's offset.
+    pub fn day_start(&self, day: Date) -> OffsetDateTime {
+        let at = Time::from_hms(self.day_start_hour, 0, 0).unwrap_or(Time::MIDNIGHT);
+        PrimitiveDateTime::new(day, at).assume_offset(self.offset)
+    }
+
+    /// First instant after journal day `day`.
+    pub fn day_end(&self, day: Date) -> OffsetDateTime {
+        self.day_start(day) + time::Duration::days(1)
+    }
+
+    /// One line for reports, e.g. `Days from 04:00 in each session's own
+    /// offset (UTC+02:00 for times in UTC)`, so readers know how sessions
+    /// were bucketed.
+    pub fn describe(&self) -> String {
+        let (h, m, _) = self.offset.as_hms();
+        format!(
+            "Days from {:02}:00 in each session's own offset (UTC{}{:02}:{:02} \
+             for times in UTC)",
+            self.day_start_hour,
+            if self.offset.is_negative() { '-' } else { '+' },
+            h.abs(),
+            m.abs()
+        )
+    }
+}
+
+#[derive(Debug)]
+struct VirtualTime {
+    wall: OffsetDateTime,
+    mono: Duration,
+}
+
+/// Virtual time: `sleep` advances the clock instantly and only yields to the
+/// scheduler, so a whole session runs in milliseconds.
+#[derive(Debug, Clone)]
+pub struct VirtualClock {
+    time: Arc<Mutex<VirtualTime>>,
+}
+
+impl VirtualClock {
+    pub fn new(start: OffsetDateTime) -> Self {
+        Self {
+            time: Arc::new(Mutex::new(VirtualTime {
+                wall: start,
+                mono: Duration::ZERO,
+            })),
+        }
+    }
+
+    fn with_time<T>(&self, f: impl FnOnce(&mut VirtualTime) -> T) -> T {
+        match self.time.lock() {
+            Ok(mut t) => f(&mut t),
+            Err(p) => f(&mut p.into_inner()),
+        }
+    }
+
+    /// Move both clocks forward without sleeping.
+    pub fn advance(&self, d: Duration) {
+        self.with_time(|t| {
+            t.wall += d;
+            t.mono += d;
+        });
+    }
+
+    /// Simulate a suspend: wall time moves on, monotonic time does not.
+    pub fn suspend(&self, d: Duration) {
+        self.with_time(|t| t.wall += d);
+    }
+
+    /// Simulate the wall clock being set (NTP, user...) by `d`, either way.
+    pub fn jump_wall(&self, d: time::Duration) {
+        self.with_time(|t| t.wall += d);
+    }
+}
+
+impl Clock for VirtualClock {
+    fn now(&self) -> OffsetDateTime {
+        self.with_time(|t| t.wall)
+    }
+
+    fn monotonic(&self) -> Duration {
+        self.with_time(|t| t.mono)
+    }
+
+    fn sleep(&self, d: Duration) -> BoxFuture<'static, ()> {
+        let clock = self.clone();
+        Box::pin(async move {
+            clock.advance(d);
+            tokio::task::yield_now().await;
+        })
+    }
+}
@@ -1,16 +1,84 @@
 // src/domain/config.rs
-use anyhow::{anyhow, Context, Result};
+use crate::domain::clock::Calendar;
+use crate::domain::layers::{self, ConfigLayer, ConfigSource, ResolvedConfig};
+use crate::domain::schedule::{Schedule, SegmentSpec};
+use crate::domain::validation::{Checker, ValidationError};
+use anyhow::Result;
 use serde::{Deserialize, Serialize};
-use std::fs;
 use std::path::Path;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
 pub struct Config {
     pub focus_min: u64,
     pub short_min: u64,
     pub long_min: u64,
     pub cycles: u8,
+    /// long break after every N cycles (besides the last one); `None`: only at the end
+    pub long_every: Option<u8>,
+    /// how many times the cycles (or the segment list) repeat in one session
+    pub rounds: u8,
+    /// repeat rounds until the session is stopped
+    pub endless: bool,
     pub task: Option<String>,
+    /// explicit segment list; when set it replaces focus/short/long/cycles
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub segments: Vec<SegmentSpec>,
+    /// Flowtime mode: open-ended focus, breaks proportional to it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub flowtime: Option<Flowtime>,
+    /// local hour (0-23) the journal day starts at, e.g. 4 for night owls
+    pub day_start_hour: u8,
+}
+
+/// Flowtime break sizing: `focus / ratio`, clamped to `min_break_min..=max_break_min`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct Flowtime {
+    pub ratio: u64,
+    pub min_break_min: u64,
+    pub max_break_min: u64,
+}
+
+impl Default for Flowtime {
+    fn default() -> Self {
+        Self {
+            ratio: 5,
+            min_break_min: 1,
+            max_break_min: 30,
+        }
+    }
+}
+
+impl Flowtime {
+    /// Break length in seconds after `focus_secs` of flow.
+    pub fn break_secs(&self, focus_secs: u64) -> u64 {
+        (focus_secs / self.ratio.max(1)).clamp(self.min_break_min * 60, self.max_break_min * 60)
+    }
+
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        let mut c = Checker::default();
+        self.check(&mut c);
+        c.finish()
+    }
+
+    fn check(&self, c: &mut Checker) {
+        c.range("flowtime.ratio", self.ratio, 1, 20, "");
+        c.range(
+            "flowtime.min_break_min",
+            self.min_break_min,
+            1,
+            60,
+            " minutes",
+        );
+        c.range(
+            "flowtime.max_break_min",
+            self.max_break_min,
+            self.min_break_min.clamp(1, 60),
+            120,
+            " minutes (and not below min_break_min)",
+        );
+    }
 }
 
 impl Default for Config {
@@ -20,85 +88,83 @@ impl Default for Config {
             short_min: 5,
             long_min: 15,
             cycles: 4,
+            long_every: None,
+            rounds: 1,
+            endless: false,
             task: None,
+            segments: Vec::new(),
+            flowtime: None,
+            day_start_hour: 0,
         }
     }
 }
 
 impl Config {
-    pub fn validate(&self) -> Result<()> {
-        if !(5..=120).contains(&self.focus_min) {
-            return Err(anyhow!("focus must be between 5 and 120 minutes"));
-        }
-        if !(1..=30).contains(&self.short_min) {
-            return Err(anyhow!("short break must be between 1 and 30 minutes"));
+    /// Check every setting the schedule uses; all failures are reported.
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        let mut c = Checker::default();
+        if let Some(t) = &self.task {
+            let n = t.chars().count();
+            if n > 80 {
+                c.fail("task", format!("{} characters", n), "at most 80 characters");
+            }
         }
-        if !(5..=60).contains(&self.long_min) {
-            return Err(anyhow!("long break must be between 5 and 60 minutes"));
+        c.range("rounds", self.rounds, 1, 12, "");
+        c.range("day_start_hour", self.day_start_hour, 0, 23, "");
+        if self.endless && self.rounds > 1 {
+            c.fail("rounds", self.rounds, "1 when endless is set");
         }
-        if !(1..=12).contains(&self.cycles) {
-            return Err(anyhow!("cycles must be between 1 and 12"));
+        if let Some(flow) = &self.flowtime {
+            if !self.segments.is_empty() || self.long_every.is_some() || self.rounds > 1 {
+                c.fail(
+                    "flowtime",
+                    "on",
+                    "off when segments, long_every or rounds are set",
+                );
+            }
+            flow.check(&mut c);
+            return c.finish();
         }
-        if let Some(t) = &self.task {
-            if t.chars().count() > 80 {
-                return Err(anyhow!("task label must be <= 80 characters"));
+        if !self.segments.is_empty() {
+            if let Some(n) = self.long_every {
+                c.fail("long_every", n, "unset with an explicit segment list");
             }
+            Schedule::from_specs(&self.segments).check(&mut c);
+            return c.finish();
         }
-        Ok(())
+        c.range("focus_min", self.focus_min, 5, 120, " minutes");
+        c.range("short_min", self.short_min, 1, 30, " minutes");
+        c.range("long_min", self.long_min, 5, 60, " minutes");
+        c.range("cycles", self.cycles, 1, 12, "");
+        if let Some(n) = self.long_every {
+            c.range("long_every", n, 1, self.cycles.max(1), " (at most cycles)");
+        }
+        c.finish()
+    }
+
+    /// Journal days in the local time zone, starting at `day_start_hour`.
+    pub fn calendar(&self) -> Calendar {
+        Calendar::local(self.day_start_hour)
     }
 
+    /// Load a preset file on its own (over the defaults).
     pub fn from_preset_file(path: &Path) -> Result<Self> {
-        let s = fs::read_to_string(path)
-            .with_context(|| format!("reading preset file {}", path.display()))?;
-        if path.extension().and_then(|s| s.to_str()) == Some("json") {
-            let cfg: Config = serde_json::from_str(&s).context("parsing json preset")?;
-            cfg.validate()?;
-            Ok(cfg)
-        } else {
-            // try yaml if feature enabled, otherwise error
-            #[cfg(feature = "serde_yaml")]
-            {
-                let cfg: Config =
-                    serde_yaml::from_str(&s).context("parsing yaml preset (serde_yaml)")?;
-                cfg.validate()?;
-                Ok(cfg)
-            }
-            #[cfg(not(feature = "serde_yaml"))]
-            {
-                Err(anyhow!(
-                    "YAML presets require building with the `serde_yaml` feature"
-                ))
-            }
-        }
+        let layers = ConfigLayer::from_file_chain(path, ConfigSource::Preset(path.to_path_buf()))?;
+        Ok(ResolvedConfig::resolve(&layers)?.config)
     }
 
+    /// Effective configuration for a run: defaults, user and project config
+    /// files, `POMODORO_*` env, `--preset` and flags (see `domain::layers`).
     pub fn from_cli_and_preset(cli: &crate::CliArgs) -> Result<Self> {
-        let mut base = if let Some(p) = &cli.preset {
-            Self::from_preset_file(p)?
-        } else {
-            Self::default()
-        };
+        Ok(layers::load(cli)?.config)
+    }
 
-        if let Some(f) = cli.focus {
-            base.focus_min = f;
-        }
-        if let Some(s) = cli.short {
-            base.short_min = s;
-        }
-        if let Some(l) = cli.long {
-            base.long_min = l;
-        }
-        if let Some(c) = cli.cycles {
-            base.cycles = c;
-        }
-        if let Some(t) = &cli.task {
-            base.task = Some(t.clone());
-        }
-        base.validate()?;
-        Ok(base)
+    /// Number of cycles the session will run over all rounds, `None` when endless.
+    pub fn total_cycles(&self) -> Option<u8> {
+        Schedule::from_config(self).cycles()
     }
 
-    pub fn into_schedule(self) -> crate::domain::schedule::Schedule {
-        crate::domain::schedule::Schedule::from_config(&self)
+    pub fn into_schedule(self) -> Schedule {
+        Schedule::from_config(&self)
     }
 }
@@ -0,0 +1,90 @@
+use crate::domain::schedule::{Segment, SegmentKind};
+use crate::infra::storage::{SegmentRecord, SessionEntry};
+use time::OffsetDateTime;
+use tokio::sync::broadcast;
+
+/// Everything the session engine reports while running. The terminal, the GUI,
+/// the notifier and the journal all follow this stream instead of driving
+/// their own segment loop.
+#[derive(Debug, Clone)]
+pub enum SessionEvent {
+    /// A new or resumed session began; `entry` is the initial snapshot.
+    SessionStarted {
+        entry: SessionEntry,
+        resumed: bool,
+    },
+    /// Segment `index` (0-based, out of `total`; `None` for endless sessions) began.
+    SegmentStarted {
+        index: usize,
+        total: Option<usize>,
+        segment: Segment,
+    },
+    /// Once per second while a segment is running (not while paused).
+    /// Open-ended segments have `planned_secs` and `remaining_secs` at 0.
+    Tick {
+        index: usize,
+        kind: SegmentKind,
+        planned_secs: u64,
+        remaining_secs: u64,
+        elapsed_secs: u64,
+    },
+    /// A segment ran to completion; `entry` is the snapshot to persist.
+    SegmentCompleted {
+        index: usize,
+        record: SegmentRecord,
+        entry: SessionEntry,
+    },
+    Paused {
+        at: OffsetDateTime,
+    },
+    Resumed {
+        at: OffsetDateTime,
+        paused_secs: u64,
+    },
+    /// A suspend or wall-clock jump was detected (see `ClockGap`).
+    ClockGap {
+        at: OffsetDateTime,
+        seconds: i64,
+    },
+    /// The session was stopped early (Ctrl-C, stop button...). Last event.
+    Interrupted {
+        entry: SessionEntry,
+    },
+    /// Every segment completed. Last event.
+    Finished {
+        entry: SessionEntry,
+    },
+    /// The engine failed before finishing. Last event.
+    Failed {
+        message: String,
+    },
+}
+
+impl SessionEvent {
+    /// No event follows this one.
+    pub fn is_last(&self) -> bool {
+        matches!(
+            self,
+            SessionEvent::Interrupted { .. }
+                | SessionEvent::Finished { .. }
+                | SessionEvent::Failed { .. }
+        )
+    }
+}
+
+/// Receiving end handed out by `SessionRunner::subscribe`.
+pub type EventReceiver = broadcast::Receiver<SessionEvent>;
+
+/// Wait for the next event, skipping over lag (slow subscribers only lose ticks
+/// in practice). Returns `None` once the engine is gone.
+pub async fn next_event(rx: &mut EventReceiver) -> Option<SessionEvent> {
+    loop {
+        match rx.recv().await {
+            Ok(ev) => return Some(ev),
+            Err(broadcast::error::RecvError::Lagged(n)) => {
+                tracing::warn!("event subscriber lagged, {} events skipped", n);
+            }
+            Err(broadcast::error::RecvError::Closed) => return None,
+        }
+    }
+}
@@ -0,0 +1,499 @@
+//! Layered configuration. Each layer only sets the keys it mentions, later
+//! layers win:
+//!
+//! ```text
+//! defaults < user file < project file < POMODORO_* env < --preset / --preset-name < CLI flags
+//! ```
+//!
+//! The user file is `config.toml` in the OS config dir (`~/.config/pomodoro/`
+//! on Linux), the project file is the nearest `.pomodoro.toml` walking up from
+//! the current directory. Both use the same keys as presets.
+
+use crate::domain::config::Config;
+use crate::infra::presets::{self, PresetStore};
+use crate::CliArgs;
+use anyhow::{anyhow, bail, Context, Result};
+use directories::ProjectDirs;
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::{debug, warn};
+
+pub const PROJECT_FILE: &str = ".pomodoro.toml";
+pub const ENV_PREFIX: &str = "POMODORO_";
+
+/// Top-level keys of `Config`, for spotting typos in config files.
+const KEYS: &[&str] = &[
+    "focus_min",
+    "short_min",
+    "long_min",
+    "cycles",
+    "long_every",
+    "rounds",
+    "endless",
+    "task",
+    "segments",
+    "flowtime",
+    "day_start_hour",
+];
+
+/// Where a configuration value came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigSource {
+    Default,
+    UserFile(PathBuf),
+    ProjectFile(PathBuf),
+    /// the environment variable that set it
+    Env(String),
+    Preset(PathBuf),
+    /// a preset shipped with the binary, by name
+    BuiltinPreset(String),
+    Cli,
+}
+
+impl fmt::Display for ConfigSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigSource::Default => write!(f, "default"),
+            ConfigSource::UserFile(p) => write!(f, "user file {}", p.display()),
+            ConfigSource::ProjectFile(p) => write!(f, "project file {}", p.display()),
+            ConfigSource::Env(var) => write!(f, "env {}", var),
+            ConfigSource::Preset(p) => write!(f, "preset {}", p.display()),
+            ConfigSource::BuiltinPreset(name) => write!(f, "built-in preset {}", name),
+            ConfigSource::Cli => write!(f, "command line"),
+        }
+    }
+}
+
+/// The keys one source sets.
+#[derive(Debug, Clone)]
+pub struct ConfigLayer {
+    pub source: ConfigSource,
+    pub values: Map<String, Value>,
+}
+
+impl ConfigLayer {
+    /// Read one config or preset file. The format comes from the extension
+    /// (`.toml`, `.json`, `.yaml`/`.yml`), or from the content for any other
+    /// name: JSON if it starts with `{`, TOML if it parses, YAML otherwise
+    /// (YAML needs the `serde_yaml` feature). `extends` is left in place, see
+    /// `from_file_chain`.
+    pub fn from_file(path: &Path, source: ConfigSource) -> Result<Self> {
+        let s = fs::read_to_string(path)
+            .with_context(|| format!("reading config file {}", path.display()))?;
+        let value: Value = match Format::detect(path, &s) {
+            Format::Toml => {
+                let table: toml::Table = toml::from_str(&s)
+                    .with_context(|| format!("parsing toml {}", path.display()))?;
+                serde_json::to_value(table)?
+            }
+            Format::Json => serde_json::from_str(&s)
+                .with_context(|| format!("parsing json {}", path.display()))?,
+            Format::Yaml => {
+                #[cfg(feature = "serde_yaml")]
+                {
+                    serde_yaml::from_str(&s)
+                        .with_context(|| format!("parsing yaml {}", path.display()))?
+                }
+                #[cfg(not(feature = "serde_yaml"))]
+                {
+                    bail!(
+                        "{} is neither TOML nor JSON, and YAML presets require building with the `serde_yaml` feature",
+                        path.display()
+                    )
+                }
+            }
+        };
+        let Value::Object(values) = value else {
+            bail!("{} must contain a table of settings", path.display());
+        };
+        Ok(Self { source, values })
+    }
+
+    /// Read `path` and the presets it `extends`, base first, so the file only
+    /// overrides what it mentions. `extends` names a file relative to the
+    /// extending one (the extension may be left out) or a built-in preset.
+    /// Extended files are attributed to `ConfigSource::Preset`.
+    pub fn from_file_chain(path: &Path, source: ConfigSource) -> Result<Vec<Self>> {
+        let mut visited: Vec<PathBuf> = Vec::new();
+        let mut layers = Vec::new();
+        let mut next = Some((path.to_path_buf(), source));
+        while let Some((file, source)) = next.take() {
+            let key = file.canonicalize().unwrap_or_else(|_| file.clone());
+            if visited.contains(&key) {
+                let chain: Vec<String> = visited
+                    .iter()
+                    .chain([&key])
+                    .map(|p| p.display().to_string())
+                    .collect();
+                bail!("preset inheritance cycle: {}", chain.join(" extends "));
+            }
+            visited.push(key);
+            let mut layer = Self::from_file(&file, source)?;
+            let parent = layer.values.remove("extends");
+            layers.push(layer);
+            match parent {
+                None => {}
+                Some(Value::String(parent)) => {
+                    match resolve_extends(&file, &parent).with_context(|| {
+                        format!("resolving extends = {:?} in {}", parent, file.display())
+                    })? {
+                        Extends::File(p) => next = Some((p.clone(), ConfigSource::Preset(p))),
+                        Extends::Builtin(base) => layers.push(base),
+                    }
+                }
+                Some(other) => bail!(
+                    "{}: extends must be a preset name or path, got {}",
+                    file.display(),
+                    other
+                ),
+            }
+        }
+        layers.reverse();
+        Ok(layers)
+    }
+
+    /// One layer per recognised `POMODORO_*` variable (sorted by name):
+    /// `FOCUS_MIN`, `SHORT_MIN`, `LONG_MIN`, `CYCLES`, `LONG_EVERY`, `ROUNDS`,
+    /// `ENDLESS`, `FLOWTIME`, `TASK`, `DAY_START_HOUR`.
+    pub fn from_env(vars: impl IntoIterator<Item = (String, String)>) -> Result<Vec<Self>> {
+        let mut vars: Vec<_> = vars
+            .into_iter()
+            .filter(|(k, _)| k.starts_with(ENV_PREFIX))
+            .collect();
+        vars.sort();
+        let mut layers = Vec::new();
+        for (var, raw) in vars {
+            let key = var[ENV_PREFIX.len()..].to_lowercase();
+            let value = match key.as_str() {
+                "focus_min" | "short_min" | "long_min" | "cycles" | "long_every" | "rounds"
+                | "day_start_hour" => {
+                    let n: u64 = raw
+                        .trim()
+                        .parse()
+                        .map_err(|_| anyhow!("{}: expected a number, got {:?}", var, raw))?;
+                    Value::from(n)
+                }
+                "endless" => Value::Bool(parse_bool(&var, &raw)?),
+                "flowtime" => {
+                    if parse_bool(&var, &raw)? {
+                        Value::Object(Map::new())
+                    } else {
+                        Value::Null
+                    }
+                }
+                "task" => Value::String(raw),
+                _ => {
+                    debug!("ignoring {}", var);
+                    continue;
+                }
+            };
+            let mut values = Map::new();
+            values.insert(key, value);
+            layers.push(Self {
+                source: ConfigSource::Env(var),
+                values,
+            });
+        }
+        Ok(layers)
+    }
+
+    /// The flags given on the command line (unset flags are not part of it).
+    pub fn from_cli(cli: &CliArgs) -> Self {
+        let mut values = Map::new();
+        let mut set = |key: &str, v: Option<Value>| {
+            if let Some(v) = v {
+                values.insert(key.to_string(), v);
+            }
+        };
+        set("focus_min", cli.focus.map(Value::from));
+        set("short_min", cli.short.map(Value::from));
+        set("long_min", cli.long.map(Value::from));
+        set("cycles", cli.cycles.map(Value::from));
+        set("long_every", cli.long_every.map(Value::from));
+        set("rounds", cli.rounds.map(Value::from));
+        set("endless", cli.endless.then_some(Value::Bool(true)));
+        set("task", cli.task.clone().map(Value::from));
+        if cli.flowtime || cli.break_ratio.is_some() {
+            let mut flow = Map::new();
+            if let Some(r) = cli.break_ratio {
+                flow.insert("ratio".to_string(), Value::from(r));
+            }
+            set("flowtime", Some(Value::Object(flow)));
+        }
+        Self {
+            source: ConfigSource::Cli,
+            values,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Format {
+    Toml,
+    Json,
+    Yaml,
+}
+
+impl Format {
+    fn detect(path: &Path, content: &str) -> Self {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("toml") => Format::Toml,
+            Some("json") => Format::Json,
+            Some("yaml" | "yml") => Format::Yaml,
+            _ if content.trim_start().starts_with('{') => Format::Json,
+            _ if toml::from_str::<toml::Table>(content).is_ok() => Format::Toml,
+            _ => Format::Yaml,
+        }
+    }
+}
+
+enum Extends {
+    File(PathBuf),
+    Builtin(ConfigLayer),
+}
+
+/// Target of `extends = "<parent>"` in `file`: a file next to it, with or
+/// without extension, else a built-in preset.
+fn resolve_extends(file: &Path, parent: &str) -> Result<Extends> {
+    let dir = file.parent().unwrap_or(Path::new("."));
+    let exact = dir.join(parent);
+    if exact.is_file() {
+        return Ok(Extends::File(exact));
+    }
+    if exact.extension().is_none() {
+        if let Some(p) = ["toml", "json", "yaml", "yml"]
+            .iter()
+            .map(|ext| exact.with_extension(ext))
+            .find(|p| p.is_file())
+        {
+            return Ok(Extends::File(p));
+        }
+    }
+    if let Some(layer) = presets::builtin_layer(parent) {
+        return Ok(Extends::Builtin(layer));
+    }
+    bail!(
+        "no preset file {} and no built-in preset of that name",
+        exact.display()
+    )
+}
+
+fn parse_bool(var: &str, raw: &str) -> Result<bool> {
+    match raw.trim().to_lowercase().as_str() {
+        "1" | "true" | "yes" | "on" => Ok(true),
+        "0" | "false" | "no" | "off" | "" => Ok(false),
+        _ => bail!("{}: expected true or false, got {:?}", var, raw),
+    }
+}
+
+/// `config.toml` in the user config dir.
+pub fn user_config_path() -> Option<PathBuf> {
+    ProjectDirs::from("com", "you", "pomodoro").map(|pd| pd.config_dir().join("config.toml"))
+}
+
+/// Nearest `.pomodoro.toml` in `start` or one of its parents.
+pub fn find_project_file(start: &Path) -> Option<PathBuf> {
+    start
+        .ancestors()
+        .map(|dir| dir.join(PROJECT_FILE))
+        .find(|p| p.is_file())
+}
+
+/// The effective configuration and the source of each value.
+#[derive(Debug, Clone)]
+pub struct ResolvedConfig {
+    pub config: Config,
+    /// dotted key (`flowtime.ratio`) -> source, for keys set by a layer
+    sources: BTreeMap<String, ConfigSource>,
+}
+
+impl ResolvedConfig {
+    /// Apply `layers` in order over the defaults and validate the result.
+    pub fn resolve(layers: &[ConfigLayer]) -> Result<Self> {
+        let resolved = Self::merge(layers)?;
+        resolved
+            .config
+            .validate()
+            .map_err(|e| e.with_sources(|field| resolved.source_of(field)))?;
+        Ok(resolved)
+    }
+
+    /// Apply `layers` in order over the defaults, without validating the
+    /// result (to show invalid values for editing).
+    pub fn merge(layers: &[ConfigLayer]) -> Result<Self> {
+        let mut merged = serde_json::to_value(Config::default())?;
+        let mut sources = BTreeMap::new();
+        for layer in layers {
+            for key in layer.values.keys() {
+                if !KEYS.contains(&key.as_str()) {
+                    warn!("unknown setting {:?} in {}", key, layer.source);
+                }
+            }
+            // report type errors against the layer that has them
+            serde_json::from_value::<Config>(Value::Object(layer.values.clone()))
+                .with_context(|| format!("invalid settings in {}", layer.source))?;
+            merge(
+                &mut merged,
+                "",
+                Value::Object(layer.values.clone()),
+                &layer.source,
+                &mut sources,
+            );
+        }
+        let config: Config = serde_json::from_value(merged).context("invalid configuration")?;
+        Ok(Self { config, sources })
+    }
+
+    /// Source of dotted `key` (`segments[2].minutes` too): the layer that set
+    /// it or one of its parents.
+    pub fn source_of(&self, key: &str) -> ConfigSource {
+        let mut k = key;
+        loop {
+            if let Some(s) = self.sources.get(k) {
+                return s.clone();
+            }
+            match k.rfind(['.', '[']) {
+                Some(i) => k = &k[..i],
+                None => return ConfigSource::Default,
+            }
+        }
+    }
+
+    /// Every effective value as `(dotted key, value, source)`, in key order.
+    pub fn describe(&self) -> Vec<(String, Value, ConfigSource)> {
+        let value = serde_json::to_value(&self.config).unwrap_or(Value::Null);
+        flatten("", &value)
+            .into_iter()
+            .map(|(k, v)| {
+                let source = self.source_of(&k);
+                (k, v, source)
+            })
+            .collect()
+    }
+}
+
+/// Resolve the configuration for `cli` from the real user file, preset store,
+/// current directory and process environment.
+pub fn load(cli: &CliArgs) -> Result<ResolvedConfig> {
+    let cwd = std::env::current_dir().context("finding current directory")?;
+    load_with(
+        cli,
+        user_config_path().as_deref(),
+        &PresetStore::open_default()?,
+        &cwd,
+        std::env::vars(),
+    )
+}
+
+/// The layers `load` applies for `cli`, lowest first.
+pub fn layers(cli: &CliArgs) -> Result<Vec<ConfigLayer>> {
+    let cwd = std::env::current_dir().context("finding current directory")?;
+    layers_with(
+        cli,
+        user_config_path().as_deref(),
+        &PresetStore::open_default()?,
+        &cwd,
+        std::env::vars(),
+    )
+}
+
+/// `load` with explicit inputs (tests, tools).
+pub fn load_with(
+    cli: &CliArgs,
+    user_file: Option<&Path>,
+    presets: &PresetStore,
+    cwd: &Path,
+    env: impl IntoIterator<Item = (String, String)>,
+) -> Result<ResolvedConfig> {
+    let resolved = ResolvedConfig::resolve(&layers_with(cli, user_file, presets, cwd, env)?)?;
+    let durations = cli.focus.is_some()
+        || cli.short.is_some()
+        || cli.long.is_some()
+        || cli.cycles.is_some()
+        || cli.long_every.is_some();
+    if durations && !resolved.config.segments.is_empty() {
+        bail!(
+            "--focus/--short/--long/--cycles/--long-every cannot be used with a configuration that lists segments (from {})",
+            resolved.source_of("segments")
+        );
+    }
+    Ok(resolved)
+}
+
+/// `layers` with explicit inputs.
+pub fn layers_with(
+    cli: &CliArgs,
+    user_file: Option<&Path>,
+    presets: &PresetStore,
+    cwd: &Path,
+    env: impl IntoIterator<Item = (String, String)>,
+) -> Result<Vec<ConfigLayer>> {
+    let mut layers = Vec::new();
+    if let Some(p) = user_file.filter(|p| p.is_file()) {
+        layers.extend(ConfigLayer::from_file_chain(
+            p,
+            ConfigSource::UserFile(p.to_path_buf()),
+        )?);
+    }
+    if let Some(p) = find_project_file(cwd) {
+        layers.extend(ConfigLayer::from_file_chain(
+            &p,
+            ConfigSource::ProjectFile(p.clone()),
+        )?);
+    }
+    layers.extend(ConfigLayer::from_env(env)?);
+    if let Some(p) = &cli.preset {
+        layers.extend(ConfigLayer::from_file_chain(
+            p,
+            ConfigSource::Preset(p.clone()),
+        )?);
+    }
+    if let Some(name) = &cli.preset_name {
+        layers.extend(presets.layers(name)?);
+    }
+    layers.push(ConfigLayer::from_cli(cli));
+    Ok(layers)
+}
+
+/// Overlay `value` on `base` at `path`: tables merge key by key, anything
+/// else replaces. Every replaced key is attributed to `source`.
+fn merge(
+    base: &mut Value,
+    path: &str,
+    value: Value,
+    source: &ConfigSource,
+    sources: &mut BTreeMap<String, ConfigSource>,
+) {
+    if let (Value::Object(b), Value::Object(v)) = (&mut *base, &value) {
+        for (k, val) in v {
+            let slot = b.entry(k.clone()).or_insert(Value::Null);
+            merge(slot, &join(path, k), val.clone(), source, sources);
+        }
+        return;
+    }
+    let prefix = format!("{}.", path);
+    sources.retain(|k, _| !k.starts_with(&prefix));
+    sources.insert(path.to_string(), source.clone());
+    *base = value;
+}
+
+fn flatten(path: &str, value: &Value) -> Vec<(String, Value)> {
+    match value {
+        Value::Object(map) if !map.is_empty() => map
+            .iter()
+            .flat_map(|(k, v)| flatten(&join(path, k), v))
+            .collect(),
+        _ => vec![(path.to_string(), value.clone())],
+    }
+}
+
+fn join(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", path, key)
+    }
+}
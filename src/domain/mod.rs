@@ -1,3 +1,9 @@
+pub mod clock;
 pub mod config;
+pub mod events;
+pub mod layers;
 pub mod schedule;
 pub mod session;
+pub mod stats;
+pub mod status;
+pub mod validation;
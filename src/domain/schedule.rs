@@ -1,77 +1,301 @@
+use crate::domain::validation::{Checker, ValidationError};
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 pub enum SegmentKind {
+    #[serde(alias = "focus")]
     Focus,
+    #[serde(alias = "short_break", alias = "break")]
     ShortBreak,
+    #[serde(alias = "long_break")]
     LongBreak,
+    /// Flowtime focus: counts up until stopped, no planned duration.
+    #[serde(alias = "flow")]
+    Flow,
+}
+
+impl SegmentKind {
+    /// Label shown in the terminal/GUI (and used by the legacy journal format).
+    pub fn label(&self) -> &'static str {
+        match self {
+            SegmentKind::Focus => "FOCUS",
+            SegmentKind::ShortBreak => "BREAK",
+            SegmentKind::LongBreak => "LONG BREAK",
+            SegmentKind::Flow => "FLOW",
+        }
+    }
+
+    /// Focus time (counted in `focus_secs`, starts a new cycle).
+    pub fn is_focus(&self) -> bool {
+        matches!(self, SegmentKind::Focus | SegmentKind::Flow)
+    }
+
+    /// No planned duration: runs until the user ends it.
+    pub fn is_open_ended(&self) -> bool {
+        *self == SegmentKind::Flow
+    }
+
+    pub fn from_label(label: &str) -> Option<Self> {
+        match label {
+            "FOCUS" => Some(SegmentKind::Focus),
+            "BREAK" => Some(SegmentKind::ShortBreak),
+            "LONG BREAK" => Some(SegmentKind::LongBreak),
+            "FLOW" => Some(SegmentKind::Flow),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Segment {
     pub kind: SegmentKind,
-    /// duration in seconds
+    /// duration in seconds; 0 for open-ended segments and for flowtime breaks
+    /// until the engine sizes them
     pub seconds: u64,
     pub cycle_index: u8,
+    /// custom label from the preset, shown instead of the kind's label
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
 }
 
+impl Segment {
+    pub fn label(&self) -> &str {
+        self.label.as_deref().unwrap_or(self.kind.label())
+    }
+
+    /// This segment repeated in round `round` (0-based) of a schedule with
+    /// `per_round` cycles per round.
+    fn in_round(&self, round: usize, per_round: u8) -> Segment {
+        let offset = u8::try_from(round * per_round as usize).unwrap_or(u8::MAX);
+        Segment {
+            cycle_index: self.cycle_index.saturating_add(offset),
+            ..self.clone()
+        }
+    }
+}
+
+/// One entry of a preset's explicit `segments` list, e.g. in YAML:
+///
+/// ```yaml
+/// segments:
+///   - { kind: focus, minutes: 10, label: Warm-up }
+///   - { kind: focus, minutes: 90, label: Deep work }
+///   - { kind: long_break, minutes: 30 }
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SegmentSpec {
+    pub kind: SegmentKind,
+    pub minutes: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+}
+
+pub const MAX_SEGMENTS: usize = 50;
+pub const MAX_SEGMENT_MINUTES: u64 = 240;
+pub const MAX_LABEL_CHARS: usize = 40;
+
 #[derive(Debug, Clone)]
 pub struct Schedule {
+    /// the whole session, or a single round when `endless`
     pub segments: Vec<Segment>,
+    /// repeat `segments` until the session is stopped
+    pub endless: bool,
 }
 
 impl Schedule {
-    /// Build a schedule from a Config (durations in minutes).
+    /// Build a schedule from a Config (durations in minutes). Flowtime is an
+    /// endless (flow, break) alternation whose breaks are sized at run time.
+    /// Otherwise one round is the preset's explicit segment list if it has
+    /// one, N × (focus, short break) otherwise, with a long break after every
+    /// `long_every` cycles and after the last one. Rounds repeat `rounds`
+    /// times (or forever when `endless`) and `cycle_index` keeps counting
+    /// across them.
     pub fn from_config(cfg: &crate::domain::config::Config) -> Self {
-        let mut segs = Vec::new();
-        for i in 1..=cfg.cycles {
-            segs.push(Segment {
-                kind: SegmentKind::Focus,
-                seconds: cfg.focus_min * 60,
-                cycle_index: i,
-            });
-            if i == cfg.cycles {
-                // add long break after last focus
-                segs.push(Segment {
-                    kind: SegmentKind::LongBreak,
-                    seconds: cfg.long_min * 60,
-                    cycle_index: i,
-                });
-            } else {
-                segs.push(Segment {
-                    kind: SegmentKind::ShortBreak,
-                    seconds: cfg.short_min * 60,
-                    cycle_index: i,
-                });
-            }
+        if cfg.flowtime.is_some() {
+            return Self::flowtime();
+        }
+        let round = if cfg.segments.is_empty() {
+            Self::round(
+                cfg.focus_min * 60,
+                cfg.short_min * 60,
+                cfg.long_min * 60,
+                cfg.cycles,
+                cfg.long_every,
+            )
+        } else {
+            Self::from_specs(&cfg.segments).segments
+        };
+        let rounds = if cfg.endless { 1 } else { cfg.rounds.max(1) };
+        let per_round = cycles_of(&round);
+        let segments = (0..rounds)
+            .flat_map(|r| {
+                round
+                    .iter()
+                    .map(move |seg| seg.in_round(r as usize, per_round))
+            })
+            .collect();
+        Schedule {
+            segments,
+            endless: cfg.endless,
         }
-        Schedule { segments: segs }
     }
 
-    /// Public helper: build a schedule using durations given in **seconds**.
-    /// Useful for tests/integration where you want short, fast-running segments.
-    pub fn from_seconds_for_test(focus_s: u64, short_s: u64, long_s: u64, cycles: u8) -> Self {
+    fn flowtime() -> Self {
+        let segment = |kind| Segment {
+            kind,
+            seconds: 0,
+            cycle_index: 1,
+            label: None,
+        };
+        Schedule {
+            segments: vec![segment(SegmentKind::Flow), segment(SegmentKind::ShortBreak)],
+            endless: true,
+        }
+    }
+
+    fn round(
+        focus_s: u64,
+        short_s: u64,
+        long_s: u64,
+        cycles: u8,
+        long_every: Option<u8>,
+    ) -> Vec<Segment> {
         let mut segs = Vec::new();
         for i in 1..=cycles {
             segs.push(Segment {
                 kind: SegmentKind::Focus,
                 seconds: focus_s,
                 cycle_index: i,
+                label: None,
             });
-            if i == cycles {
+            let long = i == cycles || long_every.is_some_and(|n| n > 0 && i % n == 0);
+            if long {
                 segs.push(Segment {
                     kind: SegmentKind::LongBreak,
                     seconds: long_s,
                     cycle_index: i,
+                    label: None,
                 });
             } else {
                 segs.push(Segment {
                     kind: SegmentKind::ShortBreak,
                     seconds: short_s,
                     cycle_index: i,
+                    label: None,
                 });
             }
         }
-        Schedule { segments: segs }
+        segs
+    }
+
+    /// Segment `index` (0-based), repeating the round when `endless`.
+    /// `None` past the end of a finite schedule.
+    pub fn segment(&self, index: usize) -> Option<Segment> {
+        if let Some(seg) = self.segments.get(index) {
+            return Some(seg.clone());
+        }
+        if !self.endless || self.segments.is_empty() {
+            return None;
+        }
+        let n = self.segments.len();
+        let seg = &self.segments[index % n];
+        Some(seg.in_round(index / n, cycles_of(&self.segments)))
+    }
+
+    /// Number of segments, `None` for an endless schedule.
+    pub fn total(&self) -> Option<usize> {
+        (!self.endless).then_some(self.segments.len())
+    }
+
+    /// Build a schedule from an explicit segment list. Each focus segment
+    /// starts a new cycle; breaks belong to the cycle before them.
+    pub fn from_specs(specs: &[SegmentSpec]) -> Self {
+        let mut cycle = 0u8;
+        let segments = specs
+            .iter()
+            .map(|spec| {
+                if spec.kind.is_focus() {
+                    cycle = cycle.saturating_add(1);
+                }
+                Segment {
+                    kind: spec.kind,
+                    seconds: spec.minutes * 60,
+                    cycle_index: cycle.max(1),
+                    label: spec.label.clone(),
+                }
+            })
+            .collect();
+        Schedule {
+            segments,
+            endless: false,
+        }
+    }
+
+    /// Number of cycles (focus segments) in the schedule, `None` when endless.
+    pub fn cycles(&self) -> Option<u8> {
+        (!self.endless).then(|| cycles_of(&self.segments))
+    }
+
+    /// Check an explicit segment list (fields reported as `segments[i].…`).
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        let mut c = Checker::default();
+        self.check(&mut c);
+        c.finish()
+    }
+
+    pub(crate) fn check(&self, c: &mut Checker) {
+        if self.segments.is_empty() {
+            c.fail("segments", "[]", "a list of at least one segment");
+        }
+        if self.segments.len() > MAX_SEGMENTS {
+            c.fail(
+                "segments",
+                format!("{} segments", self.segments.len()),
+                format!("at most {} segments", MAX_SEGMENTS),
+            );
+        }
+        if !self.segments.is_empty() && !self.segments.iter().any(|s| s.kind.is_focus()) {
+            c.fail("segments", "no focus segment", "at least one focus segment");
+        }
+        for (i, seg) in self.segments.iter().enumerate() {
+            if seg.kind.is_open_ended() {
+                c.fail(
+                    format!("segments[{}].kind", i),
+                    "Flow",
+                    "focus, short_break or long_break (flow needs flowtime mode)",
+                );
+                continue;
+            }
+            c.range(
+                format!("segments[{}].minutes", i),
+                seg.seconds / 60,
+                1,
+                MAX_SEGMENT_MINUTES,
+                " minutes",
+            );
+            if let Some(label) = &seg.label {
+                let n = label.trim().chars().count();
+                if n == 0 || n > MAX_LABEL_CHARS {
+                    c.fail(
+                        format!("segments[{}].label", i),
+                        format!("{:?}", label),
+                        format!("1 to {} characters", MAX_LABEL_CHARS),
+                    );
+                }
+            }
+        }
     }
+
+    /// Public helper: build a schedule using durations given in **seconds**.
+    /// Useful for tests/integration where you want short, fast-running segments.
+    pub fn from_seconds_for_test(focus_s: u64, short_s: u64, long_s: u64, cycles: u8) -> Self {
+        Schedule {
+            segments: Self::round(focus_s, short_s, long_s, cycles, None),
+            endless: false,
+        }
+    }
+}
+
+fn cycles_of(segments: &[Segment]) -> u8 {
+    segments.iter().map(|s| s.cycle_index).max().unwrap_or(0)
 }
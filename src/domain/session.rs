@@ -1,17 +1,161 @@
-use crate::domain::schedule::SegmentKind;
-use crate::infra::storage::{Journal, SessionEntry, SessionState};
-use crate::ui::terminal::Terminal;
+use crate::domain::clock::{SharedClock, SystemClock};
+use crate::domain::events::{EventReceiver, SessionEvent};
+use crate::domain::schedule::Segment;
+use crate::infra::status_file::StatusFile;
+use crate::infra::storage::{ClockGap, Journal, SessionEntry, SessionState};
 use anyhow::{Context, Result};
 use std::sync::{Arc, Mutex};
-use time::OffsetDateTime;
-use tracing::{error, info};
+use std::time::Duration;
+use tokio::sync::{broadcast, watch};
+use tracing::{info, warn};
 
+/// Wall/monotonic disagreement (per wake-up) above which a suspend or clock
+/// jump is recorded.
+const CLOCK_GAP_THRESHOLD_MS: i128 = 2_000;
+
+enum SegmentEnd {
+    Completed,
+    /// an open-ended segment was ended by the user
+    Ended {
+        running: Duration,
+    },
+    Skipped {
+        running: Duration,
+    },
+    Interrupted {
+        running: Duration,
+    },
+}
+
+/// Cloneable handle used by the UIs to pause/resume/stop the running session.
+/// Pauses are recorded in the current `SessionEntry`.
+#[derive(Clone)]
+pub struct SessionControl {
+    paused: Arc<watch::Sender<bool>>,
+    stopped: Arc<watch::Sender<bool>>,
+    skip: Arc<watch::Sender<bool>>,
+    state: Arc<Mutex<Option<SessionEntry>>>,
+    clock: SharedClock,
+}
+
+impl SessionControl {
+    fn new(state: Arc<Mutex<Option<SessionEntry>>>, clock: SharedClock) -> Self {
+        let (paused, _rx) = watch::channel(false);
+        let (stopped, _rx) = watch::channel(false);
+        let (skip, _rx) = watch::channel(false);
+        Self {
+            paused: Arc::new(paused),
+            stopped: Arc::new(stopped),
+            skip: Arc::new(skip),
+            state,
+            clock,
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        *self.paused.borrow()
+    }
+
+    /// Pause the running session. Returns `false` if nothing is running or already paused.
+    pub fn pause(&self) -> bool {
+        let mut guard = match self.state.lock() {
+            Ok(g) => g,
+            Err(_) => return false,
+        };
+        let Some(entry) = guard.as_mut() else {
+            return false;
+        };
+        if !entry.begin_pause(self.clock.now()) {
+            return false;
+        }
+        self.paused.send_replace(true);
+        info!("Session paused");
+        true
+    }
+
+    /// Resume a paused session. Returns `false` if the session was not paused.
+    pub fn resume(&self) -> bool {
+        let mut guard = match self.state.lock() {
+            Ok(g) => g,
+            Err(_) => return false,
+        };
+        let Some(entry) = guard.as_mut() else {
+            return false;
+        };
+        let Some(secs) = entry.end_pause(self.clock.now()) else {
+            return false;
+        };
+        self.paused.send_replace(false);
+        info!("Session resumed after {}s", secs);
+        true
+    }
+
+    pub fn toggle_pause(&self) -> bool {
+        if self.is_paused() {
+            self.resume()
+        } else {
+            self.pause()
+        }
+    }
+
+    /// Wait until the session is not paused (returns immediately if running).
+    pub async fn wait_while_paused(&self) {
+        let mut rx = self.paused.subscribe();
+        // the sender lives in `self`, so `wait_for` cannot fail
+        let _ = rx.wait_for(|paused| !*paused).await;
+    }
+
+    /// Resolves once the session is paused.
+    async fn paused(&self) {
+        let mut rx = self.paused.subscribe();
+        let _ = rx.wait_for(|paused| *paused).await;
+    }
+
+    /// End the current segment now and go on with the next one.
+    pub fn skip(&self) {
+        self.skip.send_replace(true);
+    }
+
+    async fn skip_requested(&self) {
+        let mut rx = self.skip.subscribe();
+        let _ = rx.wait_for(|skip| *skip).await;
+    }
+
+    /// Consume a pending skip request.
+    fn take_skip(&self) -> bool {
+        self.skip.send_replace(false)
+    }
+
+    /// Stop the session early; the engine records it as `Interrupted`.
+    pub fn interrupt(&self) {
+        self.stopped.send_replace(true);
+    }
+
+    pub fn is_interrupted(&self) -> bool {
+        *self.stopped.borrow()
+    }
+
+    /// Resolves once `interrupt` has been called.
+    pub async fn interrupted(&self) {
+        let mut rx = self.stopped.subscribe();
+        let _ = rx.wait_for(|stopped| *stopped).await;
+    }
+}
+
+/// The session engine: walks the schedule, keeps the `SessionEntry` up to date
+/// and broadcasts `SessionEvent`s. The journal and the notifier are attached
+/// by `run`; UIs attach with `subscribe`.
 pub struct SessionRunner {
     cfg: crate::domain::config::Config,
     journal: Journal,
     beep: bool,
     notify: bool,
     state: Arc<Mutex<Option<SessionEntry>>>,
+    control: SessionControl,
+    resume: Option<SessionEntry>,
+    clock: SharedClock,
+    events: broadcast::Sender<SessionEvent>,
+    status_file: Option<StatusFile>,
 }
 
 impl SessionRunner {
@@ -21,84 +165,348 @@ impl SessionRunner {
         beep: bool,
         notify: bool,
     ) -> Self {
+        let state = Arc::new(Mutex::new(None));
+        let clock: SharedClock = Arc::new(SystemClock);
+        let (events, _rx) = broadcast::channel(256);
         Self {
             cfg,
             journal,
             beep,
             notify,
-            state: Arc::new(Mutex::new(None)),
+            control: SessionControl::new(state.clone(), clock.clone()),
+            state,
+            resume: None,
+            clock,
+            events,
+            status_file: None,
         }
     }
 
+    /// Use `clock` for timing, journal timestamps and the journal's "today"
+    /// (e.g. a `VirtualClock` in tests). Call before handing out `control()` handles.
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.control = SessionControl::new(self.state.clone(), clock.clone());
+        self.journal = self.journal.with_clock(clock.clone());
+        self.clock = clock;
+        self
+    }
+
+    /// Keep `status_file` up to date while running (shell prompts, status bars).
+    pub fn with_status_file(mut self, status_file: StatusFile) -> Self {
+        self.status_file = Some(status_file);
+        self
+    }
+
+    /// Continue `entry` (an unfinished journal entry) instead of starting a new
+    /// session: same start/identity, restarting at the first segment that did
+    /// not finish. The entry's own config replaces the runner's.
+    pub fn resume_from(&mut self, entry: SessionEntry) {
+        self.cfg = entry.cfg.clone();
+        self.resume = Some(entry);
+    }
+
+    /// Handle to pause/resume/stop this runner from another task or thread.
+    pub fn control(&self) -> SessionControl {
+        self.control.clone()
+    }
+
+    pub fn clock(&self) -> SharedClock {
+        self.clock.clone()
+    }
+
+    /// Follow the events of the next `run`. Subscribe before calling `run`.
+    pub fn subscribe(&self) -> EventReceiver {
+        self.events.subscribe()
+    }
+
+    /// First Ctrl-C stops the session (saved as `Interrupted`), a second one exits.
     pub fn install_ctrlc_handler(&mut self) -> Result<()> {
-        let st = self.state.clone();
-        let j = self.journal.path.clone();
+        let control = self.control.clone();
         ctrlc::set_handler(move || {
-            if let Ok(mut guard) = st.lock() {
-                if let Some(entry) = guard.as_mut() {
-                    entry.state = SessionState::Interrupted;
-                    entry.end = Some(OffsetDateTime::now_utc());
-                    let _ = entry.save_to_path(&j);
-                    info!("Saved interrupted session to journal");
-                }
+            if control.is_interrupted() {
+                std::process::exit(130);
             }
-            std::process::exit(130);
+            control.interrupt();
         })
         .context("installing ctrlc handler")?;
         Ok(())
     }
 
+    /// Run the session to completion (or interruption). The journal, the
+    /// notifier and the status file follow the event stream and are flushed
+    /// before this returns.
     pub async fn run(&mut self) -> Result<SessionEntry> {
+        let journal = self.journal.clone().record_events(self.events.subscribe());
+        let notifier =
+            crate::infra::notify::notify_events(self.events.subscribe(), self.beep, self.notify);
+        let status = self.status_file.clone().map(|f| {
+            let rx = self.events.subscribe();
+            f.follow(rx, self.clock.clone())
+        });
+        let status = async {
+            if let Some(follow) = status {
+                follow.await;
+            }
+        };
+        let events = self.events.clone();
+        let engine = async {
+            let res = self.drive().await;
+            if let Err(e) = &res {
+                let _ = events.send(SessionEvent::Failed {
+                    message: format!("{:#}", e),
+                });
+            }
+            res
+        };
+        let (res, (), (), ()) = tokio::join!(engine, journal, notifier, status);
+        res
+    }
+
+    fn emit(&self, event: SessionEvent) {
+        // no subscriber is fine (e.g. headless tests)
+        let _ = self.events.send(event);
+    }
+
+    fn with_entry<T>(&self, f: impl FnOnce(&mut SessionEntry) -> T) -> Result<T> {
+        let mut guard = self
+            .state
+            .lock()
+            .map_err(|_| anyhow::anyhow!("session state poisoned"))?;
+        let entry = guard
+            .as_mut()
+            .ok_or_else(|| anyhow::anyhow!("Session state gone"))?;
+        Ok(f(entry))
+    }
+
+    async fn drive(&mut self) -> Result<SessionEntry> {
+        let (entry, skip, resumed) = match self.resume.take() {
+            Some(mut e) => {
+                e.reopen(self.clock.now());
+                let skip = e.completed_segments();
+                info!(
+                    "Resuming session started at {} from segment {}",
+                    e.start,
+                    skip + 1
+                );
+                (e, skip, true)
+            }
+            None => (
+                SessionEntry::new_with_clock(&self.cfg, &*self.clock)?,
+                0,
+                false,
+            ),
+        };
         let schedule = self.cfg.clone().into_schedule();
-        let mut terminal = Terminal::new(self.cfg.task.clone())?;
-        let entry = SessionEntry::new(&self.cfg)?;
+        let total = schedule.total();
 
-        // keep state for ctrlc
+        // shared with SessionControl (pauses)
         {
-            let mut guard = self.state.lock().unwrap();
+            let mut guard = self
+                .state
+                .lock()
+                .map_err(|_| anyhow::anyhow!("session state poisoned"))?;
             *guard = Some(entry.clone());
         }
+        self.emit(SessionEvent::SessionStarted { entry, resumed });
+
+        let mut index = skip;
+        while let Some(mut seg) = schedule.segment(index) {
+            if let Some(flow) = &self.cfg.flowtime {
+                if !seg.kind.is_focus() && seg.seconds == 0 {
+                    let focus = self.with_entry(|e| {
+                        e.segments
+                            .last()
+                            .filter(|r| r.kind.is_focus())
+                            .map_or(0, |r| r.actual_secs)
+                    })?;
+                    seg.seconds = flow.break_secs(focus);
+                }
+            }
+            let now = self.clock.now();
+            self.with_entry(|e| e.start_segment(&seg, now))?;
+            info!("Starting segment: {} ({}s)", seg.label(), seg.seconds);
+            self.emit(SessionEvent::SegmentStarted {
+                index,
+                total,
+                segment: seg.clone(),
+            });
 
-        for seg in schedule.segments {
-            let kind_label = match seg.kind {
-                SegmentKind::Focus => "FOCUS",
-                SegmentKind::ShortBreak => "BREAK",
-                SegmentKind::LongBreak => "LONG BREAK",
+            let end = self.run_segment(index, &seg).await?;
+            let now = self.clock.now();
+            let (record, entry) = match end {
+                SegmentEnd::Interrupted { running } => {
+                    return self.interrupt(Some(running.as_secs()));
+                }
+                SegmentEnd::Skipped { running } => {
+                    info!("Skipped segment: {}", seg.label());
+                    self.with_entry(|e| {
+                        e.skip_segment(now, running.as_secs());
+                        (e.segments.last().cloned(), e.clone())
+                    })?
+                }
+                SegmentEnd::Ended { running } => self.with_entry(|e| {
+                    e.end_open_segment(now, running.as_secs());
+                    (e.segments.last().cloned(), e.clone())
+                })?,
+                SegmentEnd::Completed => self.with_entry(|e| {
+                    e.finish_segment(now);
+                    (e.segments.last().cloned(), e.clone())
+                })?,
             };
-            info!("Starting segment: {} ({}s)", kind_label, seg.seconds);
-            terminal
-                .show_segment(kind_label.to_string(), seg.seconds)
-                .await?;
+            if let Some(record) = record {
+                self.emit(SessionEvent::SegmentCompleted {
+                    index,
+                    record,
+                    entry,
+                });
+            }
+            index += 1;
+        }
 
-            if self.beep {
-                crate::infra::notify::beep();
+        // finish entry
+        let entry = self.take_entry(SessionState::Completed, None)?;
+        info!("Session finished");
+        self.emit(SessionEvent::Finished {
+            entry: entry.clone(),
+        });
+        Ok(entry)
+    }
+
+    /// Count `seg` down against the monotonic clock (up, for an open-ended
+    /// segment, until a skip ends it): each wake-up targets the next whole
+    /// second of running time, so sleeping late never accumulates.
+    /// Pauses stop the count; a wall clock running ahead of the monotonic one
+    /// (suspend) is credited to the segment and recorded in the entry.
+    async fn run_segment(&self, index: usize, seg: &Segment) -> Result<SegmentEnd> {
+        let open = seg.kind.is_open_ended();
+        let planned = if open {
+            Duration::MAX
+        } else {
+            Duration::from_secs(seg.seconds)
+        };
+        let mut running = Duration::ZERO;
+        let mut last_mono = self.clock.monotonic();
+        let mut last_wall = self.clock.now();
+        let mut last_tick = 0;
+        // drop a stale skip request sent as the previous segment ended
+        self.control.take_skip();
+
+        while running < planned {
+            if self.control.is_interrupted() {
+                return Ok(SegmentEnd::Interrupted { running });
+            }
+            if self.control.take_skip() {
+                if self.control.resume() {
+                    self.emit_resumed()?;
+                }
+                if open {
+                    return Ok(SegmentEnd::Ended { running });
+                }
+                return Ok(SegmentEnd::Skipped { running });
+            }
+            if self.control.is_paused() {
+                self.emit(SessionEvent::Paused {
+                    at: self.clock.now(),
+                });
+                tokio::select! {
+                    _ = self.control.wait_while_paused() => {}
+                    _ = self.control.interrupted() => {}
+                    _ = self.control.skip_requested() => {}
+                }
+                if self.control.is_interrupted() {
+                    return Ok(SegmentEnd::Interrupted { running });
+                }
+                if self.control.is_paused() {
+                    // skipped while paused: the loop ends the pause and the segment
+                    continue;
+                }
+                self.emit_resumed()?;
+                // time spent paused is not running time
+                last_mono = self.clock.monotonic();
+                last_wall = self.clock.now();
+                continue;
             }
-            if self.notify {
-                let _ = crate::infra::notify::notify(kind_label, &self.cfg.task);
+
+            let next_tick = Duration::from_secs(running.as_secs() + 1).min(planned);
+            tokio::select! {
+                _ = self.clock.sleep(next_tick - running) => {}
+                _ = self.control.interrupted() => {}
+                _ = self.control.paused() => {}
+                _ = self.control.skip_requested() => {}
             }
 
-            // update journal partial after each segment
-            let mut guard = self.state.lock().unwrap();
-            if let Some(e) = guard.as_mut() {
-                e.segments.push(format!("{}:{}s", kind_label, seg.seconds));
-                e.last_updated = OffsetDateTime::now_utc();
-                if let Err(err) = e.append_to_path(&self.journal.path) {
-                    error!("Failed to append session partial to journal: {:?}", err);
+            let mono = self.clock.monotonic();
+            let wall = self.clock.now();
+            let mono_delta = mono.saturating_sub(last_mono);
+            running += mono_delta;
+            let gap_ms = (wall - last_wall).whole_milliseconds() - mono_delta.as_millis() as i128;
+            if gap_ms.abs() >= CLOCK_GAP_THRESHOLD_MS {
+                let seconds = (gap_ms / 1000) as i64;
+                warn!(
+                    "Wall clock moved {}s relative to the monotonic clock",
+                    seconds
+                );
+                if gap_ms > 0 {
+                    running += Duration::from_millis(gap_ms as u64);
                 }
+                self.with_entry(|e| {
+                    e.clock_gaps.push(ClockGap { at: wall, seconds });
+                })?;
+                self.emit(SessionEvent::ClockGap { at: wall, seconds });
+            }
+            last_mono = mono;
+            last_wall = wall;
+
+            let elapsed = running.min(planned).as_secs();
+            if elapsed != last_tick {
+                last_tick = elapsed;
+                self.emit(SessionEvent::Tick {
+                    index,
+                    kind: seg.kind,
+                    planned_secs: seg.seconds,
+                    remaining_secs: seg.seconds.saturating_sub(elapsed),
+                    elapsed_secs: elapsed,
+                });
             }
         }
+        Ok(SegmentEnd::Completed)
+    }
 
-        // finish entry
-        let mut guard = self.state.lock().unwrap();
-        if let Some(mut e) = guard.take() {
-            e.end = Some(OffsetDateTime::now_utc());
-            e.state = SessionState::Completed;
-            self.journal.append(&e)?;
-            info!("Session saved to journal");
-            Ok(e)
-        } else {
-            Err(anyhow::anyhow!("Session state gone"))
+    fn emit_resumed(&self) -> Result<()> {
+        let paused_secs = self.with_entry(|e| e.pauses.last().map(|p| p.seconds).unwrap_or(0))?;
+        self.emit(SessionEvent::Resumed {
+            at: self.clock.now(),
+            paused_secs,
+        });
+        Ok(())
+    }
+
+    fn interrupt(&self, running_secs: Option<u64>) -> Result<SessionEntry> {
+        let entry = self.take_entry(SessionState::Interrupted, running_secs)?;
+        warn!("Session interrupted");
+        self.emit(SessionEvent::Interrupted {
+            entry: entry.clone(),
+        });
+        Ok(entry)
+    }
+
+    fn take_entry(&self, state: SessionState, running_secs: Option<u64>) -> Result<SessionEntry> {
+        let now = self.clock.now();
+        let mut guard = self
+            .state
+            .lock()
+            .map_err(|_| anyhow::anyhow!("session state poisoned"))?;
+        let mut e = guard
+            .take()
+            .ok_or_else(|| anyhow::anyhow!("Session state gone"))?;
+        if state != SessionState::Completed {
+            e.end_pause(now);
+            e.cut_segment(now, running_secs);
         }
+        e.state = state;
+        e.end = Some(now);
+        e.last_updated = now;
+        Ok(e)
     }
 
     pub fn export_markdown(&self) -> Result<()> {
@@ -108,11 +516,20 @@ impl SessionRunner {
     pub fn export_csv(&self) -> Result<()> {
         self.journal.export_csv_today()
     }
+
+    pub fn export_ics(&self) -> Result<()> {
+        self.journal.export_ics_today()
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::SessionControl;
+    use crate::domain::clock::SystemClock;
+    use crate::domain::config::Config;
     use crate::domain::schedule::Schedule;
+    use crate::infra::storage::SessionEntry;
+    use std::sync::{Arc, Mutex};
 
     #[tokio::test]
     async fn test_short_schedule_run() {
@@ -120,4 +537,25 @@ mod tests {
         let schedule = Schedule::from_seconds_for_test(1, 1, 1, 2);
         assert_eq!(schedule.segments.len(), 4);
     }
+
+    #[tokio::test]
+    async fn test_pause_resume_records_pause() {
+        let state = Arc::new(Mutex::new(None));
+        let control = SessionControl::new(state.clone(), Arc::new(SystemClock));
+        // nothing running yet
+        assert!(!control.pause());
+
+        *state.lock().unwrap() = Some(SessionEntry::new(&Config::default()).unwrap());
+        assert!(control.pause());
+        assert!(!control.pause());
+        assert!(control.is_paused());
+        assert!(control.resume());
+        control.wait_while_paused().await;
+
+        let guard = state.lock().unwrap();
+        let entry = guard.as_ref().unwrap();
+        assert_eq!(entry.pauses.len(), 1);
+        assert!(entry.pauses[0].end.is_some());
+        assert!(!entry.is_paused());
+    }
 }
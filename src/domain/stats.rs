@@ -0,0 +1,197 @@
+//! Productivity statistics over journaled sessions: focus time per day, week
+//! and month, session outcomes, per-task totals and daily streaks.
+//!
+//! Sessions count on the journal day they started (see `Calendar`). A day
+//! belongs to a streak when it has some focus time.
+
+use crate::domain::clock::Calendar;
+use crate::infra::storage::{SessionEntry, SessionState};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use time::{Date, Duration};
+
+/// Focus time of one day (`2024-05-01`), ISO week (`2024-W18`) or month (`2024-05`).
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct PeriodTotal {
+    pub period: String,
+    pub focus_secs: u64,
+    pub sessions: usize,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct TaskTotal {
+    /// `None` for sessions without a task
+    pub task: Option<String>,
+    pub focus_secs: u64,
+    pub sessions: usize,
+}
+
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct Stats {
+    pub sessions: usize,
+    pub completed: usize,
+    pub interrupted: usize,
+    pub ongoing: usize,
+    pub focus_secs: u64,
+    /// running time (focus and breaks, pauses excluded) per session
+    pub avg_session_secs: u64,
+    pub per_day: Vec<PeriodTotal>,
+    pub per_week: Vec<PeriodTotal>,
+    pub per_month: Vec<PeriodTotal>,
+    /// most focus first
+    pub per_task: Vec<TaskTotal>,
+    /// consecutive days with focus up to `today`, or up to yesterday when
+    /// nothing was done yet today
+    pub current_streak_days: u32,
+    pub longest_streak_days: u32,
+}
+
+impl Stats {
+    /// Statistics of `entries` (one snapshot per session, as returned by
+    /// `Journal::query`), days taken in `calendar`.
+    pub fn compute(entries: &[SessionEntry], calendar: &Calendar, today: Date) -> Self {
+        let mut stats = Stats {
+            sessions: entries.len(),
+            ..Stats::default()
+        };
+        let mut days = BTreeMap::new();
+        let mut weeks = BTreeMap::new();
+        let mut months = BTreeMap::new();
+        let mut tasks: BTreeMap<Option<String>, (u64, usize)> = BTreeMap::new();
+        let mut running_secs = 0;
+
+        for e in entries {
+            match e.state {
+                SessionState::Completed => stats.completed += 1,
+                SessionState::Interrupted => stats.interrupted += 1,
+                SessionState::Ongoing => stats.ongoing += 1,
+            }
+            let focus = e.focus_secs();
+            stats.focus_secs += focus;
+            running_secs += e.segments.iter().map(|r| r.actual_secs).sum::<u64>();
+
+            let day = calendar.day_of(e.start);
+            let (iso_year, week, _) = day.to_iso_week_date();
+            add(&mut days, day.to_string(), focus);
+            add(&mut weeks, format!("{}-W{:02}", iso_year, week), focus);
+            add(
+                &mut months,
+                format!("{}-{:02}", day.year(), u8::from(day.month())),
+                focus,
+            );
+            let task = tasks.entry(e.cfg.task.clone()).or_default();
+            task.0 += focus;
+            task.1 += 1;
+        }
+        if stats.sessions > 0 {
+            stats.avg_session_secs = running_secs / stats.sessions as u64;
+        }
+
+        let focus_days: Vec<Date> = entries
+            .iter()
+            .filter(|e| e.focus_secs() > 0)
+            .map(|e| calendar.day_of(e.start))
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        stats.longest_streak_days = longest_streak(&focus_days);
+        stats.current_streak_days = current_streak(&focus_days, today);
+
+        stats.per_day = totals(days);
+        stats.per_week = totals(weeks);
+        stats.per_month = totals(months);
+        stats.per_task = tasks
+            .into_iter()
+            .map(|(task, (focus_secs, sessions))| TaskTotal {
+                task,
+                focus_secs,
+                sessions,
+            })
+            .collect();
+        stats
+            .per_task
+            .sort_by_key(|t| std::cmp::Reverse(t.focus_secs));
+        stats
+    }
+
+    /// Completed sessions out of the finished ones (completed or
+    /// interrupted), in percent.
+    pub fn completion_rate(&self) -> Option<u8> {
+        let finished = self.completed + self.interrupted;
+        (finished > 0).then(|| (self.completed * 100 / finished) as u8)
+    }
+}
+
+fn add(map: &mut BTreeMap<String, (u64, usize)>, period: String, focus: u64) {
+    let slot = map.entry(period).or_default();
+    slot.0 += focus;
+    slot.1 += 1;
+}
+
+fn totals(map: BTreeMap<String, (u64, usize)>) -> Vec<PeriodTotal> {
+    map.into_iter()
+        .map(|(period, (focus_secs, sessions))| PeriodTotal {
+            period,
+            focus_secs,
+            sessions,
+        })
+        .collect()
+}
+
+/// Focus seconds by local weekday (Monday first) and hour of the clock. A
+/// segment counts in every hour it overlaps; when it was paused, its running
+/// time is spread evenly over its wall-clock span.
+pub fn focus_heatmap(entries: &[SessionEntry], calendar: &Calendar) -> [[u64; 24]; 7] {
+    let mut grid = [[0u64; 24]; 7];
+    for r in entries.iter().flat_map(|e| &e.segments) {
+        let (Some(start), Some(end)) = (r.start, r.end) else {
+            continue;
+        };
+        let wall = (end - start).whole_seconds();
+        if !r.kind.is_focus() || wall <= 0 {
+            continue;
+        }
+        let end = calendar.local_time(end);
+        let mut t = calendar.local_time(start);
+        while t < end {
+            let next_hour =
+                t.replace_time(time::Time::MIDNIGHT) + Duration::hours(t.hour() as i64 + 1);
+            let chunk_end = next_hour.min(end);
+            let secs = (chunk_end - t).whole_seconds() as u64;
+            let cell = &mut grid[t.weekday().number_days_from_monday() as usize][t.hour() as usize];
+            *cell += secs * r.actual_secs / wall as u64;
+            t = chunk_end;
+        }
+    }
+    grid
+}
+
+/// `days` sorted, without duplicates.
+fn longest_streak(days: &[Date]) -> u32 {
+    let mut longest = 0;
+    let mut run = 0;
+    let mut prev: Option<Date> = None;
+    for &day in days {
+        run = match prev {
+            Some(p) if p + Duration::days(1) == day => run + 1,
+            _ => 1,
+        };
+        longest = longest.max(run);
+        prev = Some(day);
+    }
+    longest
+}
+
+fn current_streak(days: &[Date], today: Date) -> u32 {
+    let mut day = if days.contains(&today) {
+        today
+    } else {
+        today - Duration::days(1)
+    };
+    let mut streak = 0;
+    while days.binary_search(&day).is_ok() {
+        streak += 1;
+        day -= Duration::days(1);
+    }
+    streak
+}
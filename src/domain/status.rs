@@ -0,0 +1,235 @@
+use crate::domain::events::SessionEvent;
+use crate::domain::schedule::SegmentKind;
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum RunState {
+    #[default]
+    Idle,
+    Running,
+    Paused,
+    Finished,
+    Interrupted,
+}
+
+/// Snapshot of the session as seen from its event stream (daemon `status`).
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct SessionStatus {
+    pub state: RunState,
+    pub kind: Option<SegmentKind>,
+    /// label of the current segment (custom preset label or the kind's)
+    #[serde(default)]
+    pub label: Option<String>,
+    pub remaining_secs: u64,
+    pub planned_secs: u64,
+    /// running time of the current segment (what a flow segment shows)
+    #[serde(default)]
+    pub elapsed_secs: u64,
+    /// 0-based index of the current segment, out of `segments`
+    pub segment_index: usize,
+    /// 0 for endless sessions
+    pub segments: usize,
+    pub cycle_index: u8,
+    /// 0 for endless sessions
+    pub cycles: u8,
+    pub task: Option<String>,
+
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub started_at: Option<OffsetDateTime>,
+
+    /// when this snapshot was written (status file), used to spot stale files
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub updated_at: Option<OffsetDateTime>,
+}
+
+/// Default `--format` for the `status` command.
+pub const DEFAULT_STATUS_FORMAT: &str = "{label} {remaining} – {task}";
+
+/// Output of `status --waybar`, shaped for waybar's `custom` module
+/// (`"return-type": "json"`).
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct WaybarOutput {
+    pub text: String,
+    pub alt: String,
+    pub tooltip: String,
+    pub class: String,
+    pub percentage: u8,
+}
+
+impl SessionStatus {
+    pub fn apply(&mut self, ev: &SessionEvent) {
+        match ev {
+            SessionEvent::SessionStarted { entry, .. } => {
+                *self = SessionStatus {
+                    state: RunState::Running,
+                    cycles: entry.cfg.total_cycles().unwrap_or(0),
+                    task: entry.cfg.task.clone(),
+                    started_at: Some(entry.start),
+                    ..SessionStatus::default()
+                };
+            }
+            SessionEvent::SegmentStarted {
+                index,
+                total,
+                segment,
+            } => {
+                self.kind = Some(segment.kind);
+                self.label = Some(segment.label().to_string());
+                self.segment_index = *index;
+                self.segments = total.unwrap_or(0);
+                self.cycle_index = segment.cycle_index;
+                self.planned_secs = segment.seconds;
+                self.remaining_secs = segment.seconds;
+                self.elapsed_secs = 0;
+            }
+            SessionEvent::Tick {
+                remaining_secs,
+                elapsed_secs,
+                ..
+            } => {
+                self.remaining_secs = *remaining_secs;
+                self.elapsed_secs = *elapsed_secs;
+            }
+            SessionEvent::Paused { .. } => self.state = RunState::Paused,
+            SessionEvent::Resumed { .. } => self.state = RunState::Running,
+            SessionEvent::Finished { .. } => {
+                self.state = RunState::Finished;
+                self.remaining_secs = 0;
+            }
+            SessionEvent::Interrupted { .. } | SessionEvent::Failed { .. } => {
+                self.state = RunState::Interrupted;
+            }
+            SessionEvent::SegmentCompleted { .. } | SessionEvent::ClockGap { .. } => {}
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        matches!(self.state, RunState::Running | RunState::Paused)
+    }
+
+    fn state_name(&self) -> &'static str {
+        match self.state {
+            RunState::Idle => "idle",
+            RunState::Running => "running",
+            RunState::Paused => "paused",
+            RunState::Finished => "finished",
+            RunState::Interrupted => "interrupted",
+        }
+    }
+
+    fn label_or_kind(&self) -> &str {
+        match (&self.label, self.kind) {
+            (Some(label), _) => label,
+            (None, Some(kind)) => kind.label(),
+            (None, None) => "",
+        }
+    }
+
+    /// Seconds to show on a clock: remaining, or elapsed when counting up.
+    fn display_secs(&self) -> u64 {
+        if self.kind.is_some_and(|k| k.is_open_ended()) {
+            self.elapsed_secs
+        } else {
+            self.remaining_secs
+        }
+    }
+
+    /// Elapsed share of the current segment, 0..=100.
+    pub fn percent(&self) -> u8 {
+        if self.planned_secs == 0 {
+            return 0;
+        }
+        let done = self.planned_secs.saturating_sub(self.remaining_secs);
+        (done * 100 / self.planned_secs).min(100) as u8
+    }
+
+    /// Fill a format string. Placeholders: `{kind}`, `{label}` (custom segment
+    /// label, the kind otherwise), `{remaining}` (MM:SS),
+    /// `{remaining_secs}`, `{elapsed}` (MM:SS), `{percent}`, `{cycle}`,
+    /// `{cycles}`, `{segment}`, `{segments}`, `{task}`, `{state}`.
+    /// A trailing ` – ` left by an empty `{task}` is trimmed. `{remaining}`
+    /// shows the elapsed time of an open-ended (flow) segment.
+    pub fn render(&self, format: &str) -> String {
+        let mmss = |secs: u64| format!("{:02}:{:02}", secs / 60, secs % 60);
+        let elapsed = self.elapsed_secs;
+        let out = format
+            .replace("{kind}", self.kind.map(|k| k.label()).unwrap_or(""))
+            .replace("{label}", self.label_or_kind())
+            .replace("{remaining_secs}", &self.remaining_secs.to_string())
+            .replace("{remaining}", &mmss(self.display_secs()))
+            .replace("{elapsed}", &mmss(elapsed))
+            .replace("{percent}", &self.percent().to_string())
+            .replace("{cycles}", &or_infinite(self.cycles as usize))
+            .replace("{cycle}", &self.cycle_index.to_string())
+            .replace("{segments}", &or_infinite(self.segments))
+            .replace("{segment}", &(self.segment_index + 1).to_string())
+            .replace("{task}", self.task.as_deref().unwrap_or(""))
+            .replace("{state}", self.state_name());
+        out.trim_end_matches(" – ").trim_end().to_string()
+    }
+
+    /// Waybar JSON: `text` from `format` (empty when idle), `class`/`alt` from
+    /// the state and segment kind (e.g. `focus`, `paused`, `idle`).
+    pub fn waybar(&self, format: &str) -> WaybarOutput {
+        if !self.is_active() {
+            return WaybarOutput {
+                text: String::new(),
+                alt: self.state_name().to_string(),
+                tooltip: self.summary(),
+                class: "idle".to_string(),
+                percentage: 0,
+            };
+        }
+        let class = match (self.state, self.kind) {
+            (RunState::Paused, _) => "paused",
+            (_, Some(SegmentKind::Focus)) => "focus",
+            (_, Some(SegmentKind::ShortBreak)) => "break",
+            (_, Some(SegmentKind::LongBreak)) => "long-break",
+            (_, Some(SegmentKind::Flow)) => "flow",
+            (_, None) => "running",
+        };
+        WaybarOutput {
+            text: self.render(format),
+            alt: class.to_string(),
+            tooltip: self.summary(),
+            class: class.to_string(),
+            percentage: self.percent(),
+        }
+    }
+
+    /// One-line summary, e.g. `FOCUS 12:34 (2/4) – Study Rust [paused]`.
+    pub fn summary(&self) -> String {
+        let kind = match (self.state, self.kind) {
+            (RunState::Idle, _) => return "idle".to_string(),
+            (RunState::Finished, _) => return "finished".to_string(),
+            (RunState::Interrupted, _) => return "interrupted".to_string(),
+            (_, Some(_)) => self.label_or_kind(),
+            (_, None) => "STARTING",
+        };
+        let mut out = format!(
+            "{} {:02}:{:02} ({}/{})",
+            kind,
+            self.display_secs() / 60,
+            self.display_secs() % 60,
+            self.cycle_index,
+            or_infinite(self.cycles as usize)
+        );
+        if let Some(task) = &self.task {
+            out.push_str(&format!(" – {}", task));
+        }
+        if self.state == RunState::Paused {
+            out.push_str(" [paused]");
+        }
+        out
+    }
+}
+
+/// `n`, or `∞` for the 0 that stands for an endless session.
+fn or_infinite(n: usize) -> String {
+    if n == 0 {
+        "∞".to_string()
+    } else {
+        n.to_string()
+    }
+}
@@ -0,0 +1,118 @@
+//! Typed configuration errors: validation reports every failing field at once,
+//! so a preset can be fixed in one go and the GUI can flag each input.
+
+use crate::domain::layers::ConfigSource;
+use std::fmt;
+
+/// One failing setting. `field` is the dotted key used in config files
+/// (`focus_min`, `flowtime.ratio`, `segments[2].minutes`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldError {
+    pub field: String,
+    pub value: String,
+    /// what would be accepted, e.g. `between 5 and 120 minutes`
+    pub expected: String,
+    /// the layer that set the value, when known
+    pub source: Option<ConfigSource>,
+}
+
+impl fmt::Display for FieldError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} = {}: must be {}",
+            self.field, self.value, self.expected
+        )?;
+        if let Some(source) = &self.source {
+            write!(f, " (from {})", source)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidationError {
+    pub errors: Vec<FieldError>,
+}
+
+impl ValidationError {
+    /// The error for `field`, if it failed.
+    pub fn field(&self, field: &str) -> Option<&FieldError> {
+        self.errors.iter().find(|e| e.field == field)
+    }
+
+    /// Attribute each error to the layer that set its field.
+    pub fn with_sources(mut self, source_of: impl Fn(&str) -> ConfigSource) -> Self {
+        for e in &mut self.errors {
+            e.source = Some(source_of(&e.field));
+        }
+        self
+    }
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.errors.as_slice() {
+            [one] => write!(f, "invalid configuration: {}", one),
+            all => {
+                write!(f, "invalid configuration ({} problems):", all.len())?;
+                for e in all {
+                    write!(f, "\n  - {}", e)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+/// Collects field errors while validating.
+#[derive(Debug, Default)]
+pub(crate) struct Checker {
+    errors: Vec<FieldError>,
+}
+
+impl Checker {
+    pub(crate) fn fail(
+        &mut self,
+        field: impl Into<String>,
+        value: impl fmt::Display,
+        expected: impl Into<String>,
+    ) {
+        self.errors.push(FieldError {
+            field: field.into(),
+            value: value.to_string(),
+            expected: expected.into(),
+            source: None,
+        });
+    }
+
+    /// `value` must be in `min..=max`; `unit` is appended to the message.
+    pub(crate) fn range<T: PartialOrd + fmt::Display>(
+        &mut self,
+        field: impl Into<String>,
+        value: T,
+        min: T,
+        max: T,
+        unit: &str,
+    ) {
+        if value < min || value > max {
+            self.fail(
+                field,
+                &value,
+                format!("between {} and {}{}", min, max, unit),
+            );
+        }
+    }
+
+    pub(crate) fn finish(self) -> Result<(), ValidationError> {
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ValidationError {
+                errors: self.errors,
+            })
+        }
+    }
+}
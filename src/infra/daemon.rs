@@ -0,0 +1,408 @@
+//! Background timer daemon and its client, over a Unix domain socket.
+//!
+//! Protocol: one request per connection. The client writes one JSON object
+//! terminated by `\n`, the daemon answers with one JSON line and closes.
+//!
+//! Requests (tagged by `cmd`):
+//!
+//! ```text
+//! {"cmd":"start","focus":50,"short":10,"long":15,"cycles":3,"task":"Study Rust"}
+//! {"cmd":"pause"}   {"cmd":"resume"}   {"cmd":"skip"}   {"cmd":"stop"}   {"cmd":"status"}
+//! ```
+//!
+//! All `start` fields are optional (`focus`, `short`, `long`, `cycles`,
+//! `long_every`, `rounds`, `endless`, `flowtime`, `break_ratio`, `task`,
+//! `preset`, `preset_name`) and follow the same rules as the CLI flags.
+//! `skip` ends a flow segment.
+//!
+//! The configuration is resolved by the daemon, not the client: the user
+//! config, the `.pomodoro.toml` project file of the daemon's working directory
+//! and its `POMODORO_*` environment apply. A relative `preset` path is also
+//! resolved against the daemon's working directory, so `ctl start` sends it
+//! absolute.
+//!
+//! Responses always carry the current `SessionStatus`:
+//!
+//! ```text
+//! {"ok":true,"message":null,"status":{"state":"Running","kind":"Focus","remaining_secs":1499,...}}
+//! {"ok":false,"message":"no session running","status":{"state":"Idle",...}}
+//! ```
+//!
+//! Only one daemon runs per user: the socket lives in the user runtime dir
+//! (data dir as fallback) and a live socket makes a second daemon refuse to start.
+
+use crate::domain::events::{next_event, SessionEvent};
+use crate::domain::session::SessionControl;
+use crate::domain::status::{RunState, SessionStatus};
+use crate::infra::status_file::StatusFile;
+use crate::{CliArgs, Config, Journal, SessionRunner};
+use anyhow::{anyhow, bail, Context, Result};
+use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt};
+use tokio::net::UnixListener;
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "cmd", rename_all = "lowercase")]
+pub enum Request {
+    Start {
+        #[serde(default)]
+        focus: Option<u64>,
+        #[serde(default)]
+        short: Option<u64>,
+        #[serde(default)]
+        long: Option<u64>,
+        #[serde(default)]
+        cycles: Option<u8>,
+        #[serde(default)]
+        long_every: Option<u8>,
+        #[serde(default)]
+        rounds: Option<u8>,
+        #[serde(default)]
+        endless: bool,
+        #[serde(default)]
+        flowtime: bool,
+        #[serde(default)]
+        break_ratio: Option<u64>,
+        #[serde(default)]
+        task: Option<String>,
+        #[serde(default)]
+        preset: Option<PathBuf>,
+        #[serde(default)]
+        preset_name: Option<String>,
+    },
+    Pause,
+    Resume,
+    Skip,
+    Stop,
+    Status,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Response {
+    pub ok: bool,
+    pub message: Option<String>,
+    pub status: SessionStatus,
+}
+
+/// Default socket path: `$XDG_RUNTIME_DIR/pomodoro/pomodoro.sock` when there is a
+/// runtime dir, the data dir otherwise.
+pub fn socket_path() -> Result<PathBuf> {
+    let pd = ProjectDirs::from("com", "you", "pomodoro").context("finding project dirs")?;
+    let dir = pd.runtime_dir().unwrap_or_else(|| pd.data_dir());
+    std::fs::create_dir_all(dir).context("creating socket dir")?;
+    Ok(dir.join("pomodoro.sock"))
+}
+
+/// Send one request to the daemon listening on `socket` and wait for the answer.
+pub fn send(socket: &Path, req: &Request) -> Result<Response> {
+    let mut stream = UnixStream::connect(socket)
+        .with_context(|| format!("connecting to daemon at {}", socket.display()))?;
+    let line = serde_json::to_string(req)?;
+    writeln!(stream, "{}", line)?;
+    let mut answer = String::new();
+    BufReader::new(stream).read_line(&mut answer)?;
+    serde_json::from_str(&answer).context("parsing daemon response")
+}
+
+/// Builds a session's configuration from a `start` request.
+type ConfigLoader = Arc<dyn Fn(&CliArgs) -> Result<Config> + Send + Sync>;
+
+struct Running {
+    control: SessionControl,
+    task: JoinHandle<()>,
+}
+
+/// Hosts at most one `SessionRunner` at a time and serves the socket protocol.
+pub struct Daemon {
+    socket: PathBuf,
+    beep: bool,
+    notify: bool,
+    status: Arc<Mutex<SessionStatus>>,
+    current: Arc<Mutex<Option<Running>>>,
+    /// held for a whole `start`, so two concurrent starts cannot both find
+    /// no session running
+    starting: tokio::sync::Mutex<()>,
+    journal: Option<Journal>,
+    /// `None`: `status.json` in the data dir; `Some(None)`: no status file
+    status_file: Option<Option<PathBuf>>,
+    load_config: ConfigLoader,
+}
+
+impl Daemon {
+    pub fn new(socket: PathBuf, beep: bool, notify: bool) -> Self {
+        Self {
+            socket,
+            beep,
+            notify,
+            status: Arc::new(Mutex::new(SessionStatus::default())),
+            current: Arc::new(Mutex::new(None)),
+            starting: tokio::sync::Mutex::new(()),
+            journal: None,
+            status_file: None,
+            load_config: Arc::new(Config::from_cli_and_preset),
+        }
+    }
+
+    /// Write to `journal` instead of opening today's default journal per session.
+    pub fn with_journal(mut self, journal: Journal) -> Self {
+        self.journal = Some(journal);
+        self
+    }
+
+    /// Keep the status file at `path` up to date instead of `status.json` in
+    /// the data dir; `None` writes no status file.
+    pub fn with_status_file(mut self, path: Option<PathBuf>) -> Self {
+        self.status_file = Some(path);
+        self
+    }
+
+    /// Resolve `start` requests with `load` instead of
+    /// `Config::from_cli_and_preset` (user config, project file, env).
+    pub fn with_config_loader(
+        mut self,
+        load: impl Fn(&CliArgs) -> Result<Config> + Send + Sync + 'static,
+    ) -> Self {
+        self.load_config = Arc::new(load);
+        self
+    }
+
+    /// Serve until `shutdown` resolves; a running session is then stopped
+    /// (journaled as interrupted) and the socket removed.
+    pub async fn serve(self, shutdown: impl Future<Output = ()>) -> Result<()> {
+        let listener = bind_single(&self.socket)?;
+        info!("Daemon listening on {}", self.socket.display());
+        let daemon = Arc::new(self);
+        tokio::pin!(shutdown);
+
+        loop {
+            tokio::select! {
+                accepted = listener.accept() => {
+                    let (stream, _) = match accepted {
+                        Ok(c) => c,
+                        Err(e) => {
+                            warn!("accept failed: {}", e);
+                            continue;
+                        }
+                    };
+                    let d = daemon.clone();
+                    tokio::spawn(async move {
+                        if let Err(e) = d.handle_connection(stream).await {
+                            debug!("client error: {:#}", e);
+                        }
+                    });
+                }
+                _ = &mut shutdown => break,
+            }
+        }
+
+        info!("Daemon shutting down");
+        let running = daemon.current.lock().ok().and_then(|mut c| c.take());
+        if let Some(r) = running {
+            r.control.interrupt();
+            let _ = r.task.await;
+        }
+        let _ = std::fs::remove_file(&daemon.socket);
+        Ok(())
+    }
+
+    async fn handle_connection(&self, stream: tokio::net::UnixStream) -> Result<()> {
+        let (read, mut write) = stream.into_split();
+        let mut line = String::new();
+        tokio::io::BufReader::new(read).read_line(&mut line).await?;
+        let response = match serde_json::from_str::<Request>(&line) {
+            Ok(req) => self.handle(req).await,
+            Err(e) => self.respond(false, Some(format!("invalid request: {}", e))),
+        };
+        let mut out = serde_json::to_string(&response)?;
+        out.push('\n');
+        write.write_all(out.as_bytes()).await?;
+        Ok(())
+    }
+
+    fn respond(&self, ok: bool, message: Option<String>) -> Response {
+        let status = self.status.lock().map(|s| s.clone()).unwrap_or_default();
+        Response {
+            ok,
+            message,
+            status,
+        }
+    }
+
+    /// Control of the running session, if any (finished sessions are dropped).
+    fn control(&self) -> Option<SessionControl> {
+        let mut current = self.current.lock().ok()?;
+        if current.as_ref().is_some_and(|r| r.task.is_finished()) {
+            *current = None;
+        }
+        current.as_ref().map(|r| r.control.clone())
+    }
+
+    async fn handle(&self, req: Request) -> Response {
+        debug!("daemon request: {:?}", req);
+        let control = match req {
+            Request::Start { .. } => {
+                return match self.start(req).await {
+                    Ok(()) => self.respond(true, None),
+                    Err(e) => self.respond(false, Some(format!("{:#}", e))),
+                }
+            }
+            Request::Status => return self.respond(true, None),
+            _ => self.control(),
+        };
+        let Some(control) = control else {
+            return self.respond(false, Some("no session running".to_string()));
+        };
+        let (ok, message) = match req {
+            Request::Pause => (control.pause(), "already paused"),
+            Request::Resume => (control.resume(), "not paused"),
+            Request::Skip => {
+                control.skip();
+                (true, "")
+            }
+            Request::Stop => {
+                control.interrupt();
+                (true, "")
+            }
+            Request::Start { .. } | Request::Status => (false, "unexpected request"),
+        };
+        self.respond(ok, (!ok).then(|| message.to_string()))
+    }
+
+    /// Start a session; returns once the engine has taken over, so control
+    /// requests that follow the answer reach a live session. Starts are
+    /// serialized: a second one waits, then finds the first session running.
+    async fn start(&self, req: Request) -> Result<()> {
+        let _starting = self.starting.lock().await;
+        if self.control().is_some() {
+            bail!("a session is already running");
+        }
+        let result = self.start_session(req).await;
+        if result.is_err() {
+            if let Ok(mut s) = self.status.lock() {
+                *s = SessionStatus::default();
+            }
+        }
+        result
+    }
+
+    async fn start_session(&self, req: Request) -> Result<()> {
+        let Request::Start {
+            focus,
+            short,
+            long,
+            cycles,
+            long_every,
+            rounds,
+            endless,
+            flowtime,
+            break_ratio,
+            task,
+            preset,
+            preset_name,
+        } = req
+        else {
+            bail!("not a start request");
+        };
+        let cli = CliArgs {
+            focus,
+            short,
+            long,
+            cycles,
+            long_every,
+            rounds,
+            endless,
+            flowtime,
+            break_ratio,
+            task,
+            preset,
+            preset_name,
+            gui: false,
+            beep: self.beep,
+            notify: self.notify,
+        };
+        let cfg = (self.load_config)(&cli)?;
+        if let Ok(mut s) = self.status.lock() {
+            // until the engine's first event arrives
+            *s = SessionStatus {
+                state: RunState::Running,
+                cycles: cfg.total_cycles().unwrap_or(0),
+                task: cfg.task.clone(),
+                ..SessionStatus::default()
+            };
+        }
+        let journal = match &self.journal {
+            Some(j) => j.clone(),
+            None => Journal::open_default(cfg.calendar()).context("opening journal")?,
+        };
+        let mut runner = SessionRunner::new(cfg, journal, self.beep, self.notify);
+        match &self.status_file {
+            None => match StatusFile::open_default() {
+                Ok(f) => runner = runner.with_status_file(f),
+                Err(e) => warn!("status file disabled: {:#}", e),
+            },
+            Some(Some(path)) => runner = runner.with_status_file(StatusFile { path: path.clone() }),
+            Some(None) => {}
+        }
+        let control = runner.control();
+        let mut started = runner.subscribe();
+
+        // status follows the event stream
+        let mut rx = runner.subscribe();
+        let status = self.status.clone();
+        tokio::spawn(async move {
+            while let Some(ev) = next_event(&mut rx).await {
+                if let Ok(mut s) = status.lock() {
+                    s.apply(&ev);
+                }
+                if ev.is_last() {
+                    break;
+                }
+            }
+        });
+
+        let task = tokio::spawn(async move {
+            match runner.run().await {
+                Ok(e) => info!("Session ended: {:?}", e.state),
+                Err(e) => warn!("Session ended with error: {:?}", e),
+            }
+        });
+        {
+            let mut current = self
+                .current
+                .lock()
+                .map_err(|_| anyhow!("daemon state poisoned"))?;
+            *current = Some(Running { control, task });
+        }
+
+        while let Some(ev) = next_event(&mut started).await {
+            if let SessionEvent::Failed { message } = &ev {
+                bail!("{}", message);
+            }
+            if matches!(ev, SessionEvent::SessionStarted { .. }) || ev.is_last() {
+                break;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Bind `socket`, refusing if another daemon answers on it and cleaning up a
+/// stale socket file left by a crash.
+fn bind_single(socket: &Path) -> Result<UnixListener> {
+    if socket.exists() {
+        if UnixStream::connect(socket).is_ok() {
+            bail!("a daemon is already running on {}", socket.display());
+        }
+        std::fs::remove_file(socket)
+            .with_context(|| format!("removing stale socket {}", socket.display()))?;
+    }
+    UnixListener::bind(socket).with_context(|| format!("binding {}", socket.display()))
+}
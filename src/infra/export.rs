@@ -0,0 +1,437 @@
+//! Journal exports: sessions of a day range rendered as Markdown, CSV,
+//! iCalendar or an HTML report (see `report`) and written to a file or to
+//! stdout. Times are shown in the journal calendar's offset.
+//!
+//! CSV follows RFC 4180: a header row, CRLF line ends, and fields holding a
+//! comma, a quote or a line break are quoted with inner quotes doubled.
+//!
+//! iCalendar (RFC 5545) has one VEVENT per focus segment or per session, in
+//! UTC. UIDs derive from the session id (and segment index), so importing the
+//! same sessions again updates the events instead of adding copies.
+
+use crate::domain::clock::Calendar;
+use crate::infra::report;
+use crate::infra::storage::{timestamp, JournalQuery, SessionEntry};
+use anyhow::{bail, Context, Result};
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::str::FromStr;
+use time::Date;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Markdown,
+    /// one row per session
+    Csv,
+    /// one row per segment
+    SegmentsCsv,
+    /// one event per focus segment
+    Ics,
+    /// one event per session
+    SessionsIcs,
+    /// report with charts
+    Html,
+}
+
+impl FromStr for ExportFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "md" | "markdown" => Ok(ExportFormat::Markdown),
+            "csv" => Ok(ExportFormat::Csv),
+            "segments-csv" => Ok(ExportFormat::SegmentsCsv),
+            "ics" => Ok(ExportFormat::Ics),
+            "sessions-ics" => Ok(ExportFormat::SessionsIcs),
+            "html" => Ok(ExportFormat::Html),
+            _ => bail!(
+                "unknown export format {:?} (expected md, csv, segments-csv, ics, sessions-ics or html)",
+                s
+            ),
+        }
+    }
+}
+
+impl ExportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Markdown => "md",
+            ExportFormat::Csv | ExportFormat::SegmentsCsv => "csv",
+            ExportFormat::Ics | ExportFormat::SessionsIcs => "ics",
+            ExportFormat::Html => "html",
+        }
+    }
+
+    /// `entries` in this format; `title` names the exported period, `today`
+    /// ends the report's streaks.
+    pub fn render(
+        &self,
+        title: &str,
+        entries: &[SessionEntry],
+        calendar: &Calendar,
+        today: Date,
+    ) -> String {
+        match self {
+            ExportFormat::Markdown => markdown(title, entries, calendar),
+            ExportFormat::Csv => csv(entries, calendar),
+            ExportFormat::SegmentsCsv => segments_csv(entries, calendar),
+            ExportFormat::Ics => ics(entries, IcsEvents::FocusSegments),
+            ExportFormat::SessionsIcs => ics(entries, IcsEvents::Sessions),
+            ExportFormat::Html => report::html(title, entries, calendar, today),
+        }
+    }
+}
+
+/// Where an export goes: `-` is stdout, anything else a file path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Output {
+    Stdout,
+    File(PathBuf),
+}
+
+impl FromStr for Output {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "" => bail!("empty output path (use - for stdout)"),
+            "-" => Ok(Output::Stdout),
+            path => Ok(Output::File(PathBuf::from(path))),
+        }
+    }
+}
+
+impl Output {
+    /// Write `body`, replacing the file if there is one.
+    pub fn write(&self, body: &str) -> Result<()> {
+        match self {
+            Output::Stdout => {
+                let mut out = std::io::stdout().lock();
+                out.write_all(body.as_bytes())
+                    .and_then(|()| out.flush())
+                    .context("writing export to stdout")
+            }
+            Output::File(path) => {
+                fs::write(path, body).with_context(|| format!("writing {}", path.display()))
+            }
+        }
+    }
+}
+
+/// Human name of the days `query` selects, e.g. `2024-05-01 → 2024-05-07`.
+pub fn period(query: &JournalQuery, today: Date) -> String {
+    match (query.from, query.to) {
+        (Some(f), Some(t)) if f == t && f == today => "today".to_string(),
+        (Some(f), Some(t)) if f == t => f.to_string(),
+        (Some(f), Some(t)) => format!("{} → {}", f, t),
+        (Some(f), None) => format!("since {}", f),
+        (None, Some(t)) => format!("until {}", t),
+        (None, None) => "all days".to_string(),
+    }
+}
+
+pub fn markdown(title: &str, entries: &[SessionEntry], calendar: &Calendar) -> String {
+    let cal = calendar;
+    let mut md = format!("# Pomodoro journal ({})\n\n_{}_\n\n", title, cal.describe());
+    for e in entries {
+        md.push_str(&format!(
+            "- **start**: {}\n  - id: {}\n  - task: {:?}\n  - state: {:?}\n  - focus: {}s (paused {}s)\n",
+            timestamp(cal.local_time(e.start)),
+            e.id,
+            e.cfg.task,
+            e.state,
+            e.focus_secs(),
+            e.paused_secs
+        ));
+        for g in &e.clock_gaps {
+            md.push_str(&format!(
+                "  - clock gap: {:+}s at {}\n",
+                g.seconds,
+                cal.local_time(g.at).time()
+            ));
+        }
+        md.push_str("  - segments:\n");
+        for r in &e.segments {
+            let when = match (r.start, r.end) {
+                (Some(s), Some(end)) => format!(
+                    " ({} → {})",
+                    cal.local_time(s).time(),
+                    cal.local_time(end).time()
+                ),
+                (Some(s), None) => format!(" ({} → …)", cal.local_time(s).time()),
+                _ => String::new(),
+            };
+            md.push_str(&format!(
+                "    - {} #{}: {}{}\n",
+                r.label(),
+                r.cycle_index,
+                r.duration_text(),
+                when
+            ));
+        }
+        md.push('\n');
+    }
+    md
+}
+
+/// Columns of `csv`.
+pub const CSV_HEADER: &[&str] = &[
+    "id",
+    "start",
+    "end",
+    "state",
+    "task",
+    "planned_focus_min",
+    "focus_min",
+    "completed_focus",
+    "interruptions",
+];
+
+/// Columns of `segments_csv`.
+pub const SEGMENTS_CSV_HEADER: &[&str] = &[
+    "session_id",
+    "task",
+    "cycle",
+    "kind",
+    "label",
+    "start",
+    "end",
+    "planned_min",
+    "actual_min",
+    "skipped",
+];
+
+/// One row per session, times in local time.
+pub fn csv(entries: &[SessionEntry], calendar: &Calendar) -> String {
+    let mut out = CsvWriter::new(CSV_HEADER);
+    for e in entries {
+        out.row([
+            e.id.to_string(),
+            timestamp(calendar.local_time(e.start)),
+            e.end
+                .map(|d| timestamp(calendar.local_time(d)))
+                .unwrap_or_default(),
+            format!("{:?}", e.state),
+            e.cfg.task.clone().unwrap_or_default(),
+            minutes(e.planned_focus_secs()),
+            minutes(e.focus_secs()),
+            e.completed_focus().to_string(),
+            e.interruptions().to_string(),
+        ]);
+    }
+    out.finish()
+}
+
+/// One row per segment that ran, with its session's id and task.
+pub fn segments_csv(entries: &[SessionEntry], calendar: &Calendar) -> String {
+    let time = |t: Option<time::OffsetDateTime>| {
+        t.map(|t| timestamp(calendar.local_time(t)))
+            .unwrap_or_default()
+    };
+    let mut out = CsvWriter::new(SEGMENTS_CSV_HEADER);
+    for e in entries {
+        for r in &e.segments {
+            out.row([
+                e.id.to_string(),
+                e.cfg.task.clone().unwrap_or_default(),
+                r.cycle_index.to_string(),
+                r.kind.label().to_string(),
+                r.label().to_string(),
+                time(r.start),
+                time(r.end),
+                minutes(r.planned_secs),
+                minutes(r.actual_secs),
+                r.skipped.to_string(),
+            ]);
+        }
+    }
+    out.finish()
+}
+
+/// What one iCalendar event stands for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IcsEvents {
+    /// each focus (or flow) segment that ended
+    FocusSegments,
+    /// each session, from its start to its end (or last update)
+    Sessions,
+}
+
+/// iCalendar of `entries`. Segments journaled without times (legacy format)
+/// and segments still running are left out.
+pub fn ics(entries: &[SessionEntry], events: IcsEvents) -> String {
+    let mut out = IcsWriter::default();
+    out.line("BEGIN:VCALENDAR");
+    out.line("VERSION:2.0");
+    out.line("PRODID:-//pomodoro-cli//journal export//EN");
+    out.line("CALSCALE:GREGORIAN");
+    out.line("METHOD:PUBLISH");
+    for e in entries {
+        let task = e.cfg.task.as_deref().unwrap_or("Pomodoro");
+        let cycles = e.cfg.clone().into_schedule().cycles();
+        match events {
+            IcsEvents::Sessions => {
+                let description = format!(
+                    "Session {:?}\n{}, {} focus completed\nfocus {} min, paused {} min",
+                    e.state,
+                    e.progress(),
+                    e.completed_focus(),
+                    e.focus_secs() / 60,
+                    e.paused_secs / 60
+                );
+                out.event(
+                    &format!("{}@pomodoro-cli", e.id),
+                    e,
+                    e.start,
+                    e.end.unwrap_or(e.last_updated),
+                    task,
+                    &description,
+                );
+            }
+            IcsEvents::FocusSegments => {
+                for (i, r) in e.segments.iter().enumerate() {
+                    let (Some(start), Some(end)) = (r.start, r.end) else {
+                        continue;
+                    };
+                    if !r.kind.is_focus() {
+                        continue;
+                    }
+                    let cycle = match cycles {
+                        Some(n) => format!("cycle {}/{}", r.cycle_index, n),
+                        None => format!("cycle {}", r.cycle_index),
+                    };
+                    let outcome = if r.skipped {
+                        "skipped"
+                    } else if r.is_complete() {
+                        "completed"
+                    } else {
+                        "cut short"
+                    };
+                    let description = format!(
+                        "{} {}, {} ({} min)\nSession {:?}",
+                        r.label(),
+                        cycle,
+                        outcome,
+                        r.actual_secs / 60,
+                        e.state
+                    );
+                    out.event(
+                        &format!("{}-{}@pomodoro-cli", e.id, i),
+                        e,
+                        start,
+                        end,
+                        task,
+                        &description,
+                    );
+                }
+            }
+        }
+    }
+    out.line("END:VCALENDAR");
+    out.out
+}
+
+/// RFC 5545 content lines: CRLF ends, folded at 75 octets.
+#[derive(Default)]
+struct IcsWriter {
+    out: String,
+}
+
+impl IcsWriter {
+    fn line(&mut self, line: &str) {
+        let mut width = 0;
+        for c in line.chars() {
+            if width + c.len_utf8() > 75 {
+                self.out.push_str("\r\n ");
+                width = 1;
+            }
+            self.out.push(c);
+            width += c.len_utf8();
+        }
+        self.out.push_str("\r\n");
+    }
+
+    fn event(
+        &mut self,
+        uid: &str,
+        e: &SessionEntry,
+        start: time::OffsetDateTime,
+        end: time::OffsetDateTime,
+        summary: &str,
+        description: &str,
+    ) {
+        self.line("BEGIN:VEVENT");
+        self.line(&format!("UID:{}", uid));
+        // a later snapshot of the session is a newer version of the event
+        self.line(&format!("DTSTAMP:{}", ics_time(e.last_updated)));
+        self.line(&format!("LAST-MODIFIED:{}", ics_time(e.last_updated)));
+        self.line(&format!("DTSTART:{}", ics_time(start)));
+        self.line(&format!("DTEND:{}", ics_time(end.max(start))));
+        self.line(&format!("SUMMARY:{}", ics_text(summary)));
+        self.line(&format!("DESCRIPTION:{}", ics_text(description)));
+        self.line("TRANSP:OPAQUE");
+        self.line("END:VEVENT");
+    }
+}
+
+/// `20240502T090000Z`
+fn ics_time(t: time::OffsetDateTime) -> String {
+    let t = t.to_offset(time::UtcOffset::UTC);
+    format!(
+        "{:04}{:02}{:02}T{:02}{:02}{:02}Z",
+        t.year(),
+        u8::from(t.month()),
+        t.day(),
+        t.hour(),
+        t.minute(),
+        t.second()
+    )
+}
+
+/// TEXT value escaping: backslash, `;`, `,` and line breaks.
+fn ics_text(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+/// Minutes with one decimal, e.g. `12.5`.
+fn minutes(secs: u64) -> String {
+    format!("{:.1}", secs as f64 / 60.0)
+}
+
+/// RFC 4180 writer.
+struct CsvWriter {
+    out: String,
+}
+
+impl CsvWriter {
+    fn new(header: &[&str]) -> Self {
+        let mut w = CsvWriter { out: String::new() };
+        w.row(header.iter().map(|h| h.to_string()));
+        w
+    }
+
+    fn row(&mut self, fields: impl IntoIterator<Item = String>) {
+        for (i, field) in fields.into_iter().enumerate() {
+            if i > 0 {
+                self.out.push(',');
+            }
+            if field.contains([',', '"', '\r', '\n']) {
+                self.out.push('"');
+                self.out.push_str(&field.replace('"', "\"\""));
+                self.out.push('"');
+            } else {
+                self.out.push_str(&field);
+            }
+        }
+        self.out.push_str("\r\n");
+    }
+
+    fn finish(self) -> String {
+        self.out
+    }
+}
@@ -1,2 +1,10 @@
+#[cfg(unix)]
+pub mod daemon;
+pub mod export;
 pub mod notify;
+pub mod presets;
+pub mod report;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+pub mod status_file;
 pub mod storage;
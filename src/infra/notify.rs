@@ -1,3 +1,4 @@
+use crate::domain::events::{next_event, EventReceiver, SessionEvent};
 use anyhow::Result;
 use std::io::Write;
 use std::process::Command;
@@ -98,3 +99,25 @@ pub fn notify(title: &str, body: &Option<String>) -> Result<()> {
         Ok(())
     }
 }
+
+/// Notifier subscriber: beep and/or notify when a segment completes.
+pub async fn notify_events(mut rx: EventReceiver, beep_on: bool, notify_on: bool) {
+    let mut task = None;
+    while let Some(ev) = next_event(&mut rx).await {
+        match &ev {
+            SessionEvent::SessionStarted { entry, .. } => task = entry.cfg.task.clone(),
+            SessionEvent::SegmentCompleted { record, .. } => {
+                if beep_on {
+                    beep();
+                }
+                if notify_on {
+                    let _ = notify(record.label(), &task);
+                }
+            }
+            _ => {}
+        }
+        if ev.is_last() {
+            break;
+        }
+    }
+}
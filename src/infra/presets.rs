@@ -0,0 +1,231 @@
+use crate::domain::config::Config;
+use crate::domain::layers::{ConfigLayer, ConfigSource, ResolvedConfig};
+use anyhow::{anyhow, bail, Context, Result};
+use directories::ProjectDirs;
+use serde_json::{json, Value};
+use std::fs;
+use std::path::PathBuf;
+
+/// Extensions a user preset file may have, in lookup order.
+const EXTENSIONS: &[&str] = &["toml", "json", "yaml", "yml"];
+
+/// Settings `save` writes: the timer and schedule. Presets rank above the
+/// user and project files, so the task and the day start stay theirs.
+const SAVED_KEYS: &[&str] = &[
+    "focus_min",
+    "short_min",
+    "long_min",
+    "cycles",
+    "long_every",
+    "rounds",
+    "endless",
+    "segments",
+    "flowtime",
+];
+
+/// Presets shipped with the binary: name, description, settings. Only the keys
+/// listed are set, so they layer like a preset file.
+fn builtins() -> Vec<(&'static str, &'static str, Value)> {
+    vec![
+        (
+            "classic",
+            "25 min focus, 5 min breaks, 15 min long break after 4",
+            json!({ "focus_min": 25, "short_min": 5, "long_min": 15, "cycles": 4 }),
+        ),
+        (
+            "50-10",
+            "50 min focus, 10 min breaks, 20 min long break after 3",
+            json!({ "focus_min": 50, "short_min": 10, "long_min": 20, "cycles": 3 }),
+        ),
+        (
+            "ultradian",
+            "90 min focus, 20 min breaks, 30 min long break after 3",
+            json!({ "focus_min": 90, "short_min": 20, "long_min": 30, "cycles": 3 }),
+        ),
+    ]
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PresetInfo {
+    pub name: String,
+    pub builtin: bool,
+    /// file of a user preset
+    pub path: Option<PathBuf>,
+    pub description: String,
+}
+
+/// Named presets: the built-ins plus files in `presets/` under the config dir
+/// (`<name>.toml`, `.json`, `.yaml`). A user preset cannot reuse a built-in name.
+#[derive(Debug, Clone)]
+pub struct PresetStore {
+    pub dir: PathBuf,
+}
+
+impl PresetStore {
+    pub fn open_default() -> Result<Self> {
+        let pd = ProjectDirs::from("com", "you", "pomodoro").context("finding project dirs")?;
+        Ok(PresetStore {
+            dir: pd.config_dir().join("presets"),
+        })
+    }
+
+    /// Built-ins first, then user presets by name.
+    pub fn list(&self) -> Result<Vec<PresetInfo>> {
+        let mut out: Vec<PresetInfo> = builtins()
+            .into_iter()
+            .map(|(name, description, _)| PresetInfo {
+                name: name.to_string(),
+                builtin: true,
+                path: None,
+                description: description.to_string(),
+            })
+            .collect();
+        let mut user = Vec::new();
+        if self.dir.is_dir() {
+            for entry in fs::read_dir(&self.dir).context("reading presets dir")? {
+                let path = entry?.path();
+                let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+                let Some(name) = path.file_stem().and_then(|s| s.to_str()) else {
+                    continue;
+                };
+                if !EXTENSIONS.contains(&ext) || is_builtin(name) {
+                    continue;
+                }
+                let description = match self.load(name) {
+                    Ok(cfg) => summary(&cfg),
+                    Err(e) => format!("invalid: {:#}", e),
+                };
+                user.push(PresetInfo {
+                    name: name.to_string(),
+                    builtin: false,
+                    path: Some(path),
+                    description,
+                });
+            }
+        }
+        user.sort_by(|a, b| a.name.cmp(&b.name));
+        out.extend(user);
+        Ok(out)
+    }
+
+    /// The settings of preset `name` as configuration layers, the presets it
+    /// `extends` first.
+    pub fn layers(&self, name: &str) -> Result<Vec<ConfigLayer>> {
+        if let Some(layer) = builtin_layer(name) {
+            return Ok(vec![layer]);
+        }
+        let path = self
+            .path_of(name)
+            .ok_or_else(|| anyhow!("no preset named {:?} (see `preset list`)", name))?;
+        ConfigLayer::from_file_chain(&path, ConfigSource::Preset(path.clone()))
+    }
+
+    /// Preset `name` over the defaults, validated.
+    pub fn load(&self, name: &str) -> Result<Config> {
+        Ok(ResolvedConfig::resolve(&self.layers(name)?)?.config)
+    }
+
+    /// Save the timer and schedule of `cfg` as `<name>.toml`. Refuses built-in names, and existing
+    /// presets unless `force`. The old preset stays in place until the new
+    /// one is written (temp file + rename); a replaced `.json`/`.yaml` file
+    /// is removed afterwards.
+    pub fn save(&self, name: &str, cfg: &Config, force: bool) -> Result<PathBuf> {
+        check_name(name)?;
+        if is_builtin(name) {
+            bail!("{:?} is a built-in preset, choose another name", name);
+        }
+        let existing = self.path_of(name);
+        if let (Some(existing), false) = (&existing, force) {
+            bail!(
+                "preset {:?} already exists ({}), use --force to replace it",
+                name,
+                existing.display()
+            );
+        }
+        cfg.validate()?;
+        fs::create_dir_all(&self.dir).context("creating presets dir")?;
+        let path = self.dir.join(format!("{}.toml", name));
+        let tmp = path.with_extension("toml.tmp");
+        let mut settings = toml::Value::try_from(cfg).context("serializing preset")?;
+        if let Some(table) = settings.as_table_mut() {
+            table.retain(|key, _| SAVED_KEYS.contains(&key));
+        }
+        let body = toml::to_string(&settings).context("serializing preset")?;
+        fs::write(&tmp, body).with_context(|| format!("writing {}", tmp.display()))?;
+        fs::rename(&tmp, &path).with_context(|| format!("replacing {}", path.display()))?;
+        for ext in EXTENSIONS.iter().filter(|ext| **ext != "toml") {
+            let other = self.dir.join(format!("{}.{}", name, ext));
+            if other.is_file() {
+                fs::remove_file(&other).with_context(|| format!("removing {}", other.display()))?;
+            }
+        }
+        Ok(path)
+    }
+
+    pub fn delete(&self, name: &str) -> Result<PathBuf> {
+        if is_builtin(name) {
+            bail!("{:?} is a built-in preset and cannot be deleted", name);
+        }
+        let path = self
+            .path_of(name)
+            .ok_or_else(|| anyhow!("no preset named {:?}", name))?;
+        fs::remove_file(&path).with_context(|| format!("removing {}", path.display()))?;
+        Ok(path)
+    }
+
+    fn path_of(&self, name: &str) -> Option<PathBuf> {
+        check_name(name).ok()?;
+        EXTENSIONS
+            .iter()
+            .map(|ext| self.dir.join(format!("{}.{}", name, ext)))
+            .find(|p| p.is_file())
+    }
+}
+
+/// The built-in preset `name`, as a layer.
+pub fn builtin_layer(name: &str) -> Option<ConfigLayer> {
+    builtins()
+        .into_iter()
+        .find(|(n, _, _)| *n == name)
+        .and_then(|(_, _, values)| match values {
+            Value::Object(values) => Some(ConfigLayer {
+                source: ConfigSource::BuiltinPreset(name.to_string()),
+                values,
+            }),
+            _ => None,
+        })
+}
+
+fn is_builtin(name: &str) -> bool {
+    builtins().iter().any(|(n, _, _)| *n == name)
+}
+
+/// Lowercase letters, digits, `-` and `_`; up to 40 characters.
+fn check_name(name: &str) -> Result<()> {
+    let ok = !name.is_empty()
+        && name.len() <= 40
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_');
+    if !ok {
+        bail!(
+            "invalid preset name {:?}: use up to 40 lowercase letters, digits, '-' or '_'",
+            name
+        );
+    }
+    Ok(())
+}
+
+/// One-line description of a configuration, e.g. `50/10/20 min ×3`.
+fn summary(cfg: &Config) -> String {
+    if cfg.flowtime.is_some() {
+        return "flowtime".to_string();
+    }
+    if !cfg.segments.is_empty() {
+        return format!("{} segments", cfg.segments.len());
+    }
+    format!(
+        "{}/{}/{} min ×{}",
+        cfg.focus_min, cfg.short_min, cfg.long_min, cfg.cycles
+    )
+}
@@ -0,0 +1,323 @@
+//! HTML report: one self-contained file (inline CSS and SVG, no scripts or
+//! external resources) with focus minutes per day, a weekday × hour heatmap,
+//! focus per task and the completed/interrupted ratio of a day range.
+
+use crate::domain::clock::Calendar;
+use crate::domain::stats::{focus_heatmap, Stats};
+use crate::infra::storage::SessionEntry;
+use std::fmt::Write;
+use time::{Date, Duration};
+
+const WIDTH: u32 = 720;
+const FOCUS_COLOR: &str = "#d9534f";
+const INTERRUPTED_COLOR: &str = "#f0ad4e";
+const ONGOING_COLOR: &str = "#999";
+/// Tasks shown in the per-task chart; the rest are summed up as "other".
+const MAX_TASKS: usize = 10;
+
+const STYLE: &str = "
+body { font-family: system-ui, sans-serif; max-width: 760px; margin: 2em auto; color: #222; }
+h1 { font-size: 1.5em; } h2 { font-size: 1.1em; margin-top: 2em; }
+.summary { display: flex; gap: 2em; } .summary div { font-size: 0.9em; color: #555; }
+.summary b { display: block; font-size: 1.6em; color: #222; }
+.note { margin-top: 2em; font-size: 0.8em; color: #777; }
+svg text { font-size: 11px; fill: #444; }
+";
+
+/// The report of `entries` (one snapshot per session, as returned by
+/// `Journal::query`); `title` names the period, `today` ends the streaks.
+pub fn html(title: &str, entries: &[SessionEntry], calendar: &Calendar, today: Date) -> String {
+    let stats = Stats::compute(entries, calendar, today);
+    let mut out = String::new();
+    let _ = write!(
+        out,
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>Pomodoro report ({title})</title>\n<style>{STYLE}</style>\n</head>\n<body>\n\
+         <h1>Pomodoro report ({title})</h1>\n",
+        title = escape(title),
+    );
+    summary(&mut out, &stats);
+    if entries.is_empty() {
+        out.push_str("<p>No sessions in this period.</p>\n");
+    } else {
+        out.push_str("<h2>Focus minutes per day</h2>\n");
+        per_day_chart(&mut out, &stats);
+        out.push_str("<h2>Focus by time of day</h2>\n");
+        heatmap(&mut out, &focus_heatmap(entries, calendar));
+        out.push_str("<h2>Focus per task</h2>\n");
+        per_task_chart(&mut out, &stats);
+        out.push_str("<h2>Completed vs interrupted</h2>\n");
+        outcome_chart(&mut out, &stats);
+    }
+    let _ = writeln!(
+        out,
+        "<p class=\"note\">{}</p>",
+        escape(&calendar.describe())
+    );
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+fn summary(out: &mut String, stats: &Stats) {
+    let rate = stats
+        .completion_rate()
+        .map(|r| format!("{}%", r))
+        .unwrap_or_else(|| "–".to_string());
+    let _ = writeln!(
+        out,
+        "<div class=\"summary\">\
+         <div><b>{}</b>sessions</div>\
+         <div><b>{}</b>focus</div>\
+         <div><b>{}</b>completed</div>\
+         <div><b>{} d</b>longest streak</div>\
+         </div>",
+        stats.sessions,
+        hours_minutes(stats.focus_secs),
+        rate,
+        stats.longest_streak_days
+    );
+}
+
+/// One bar per day from the first to the last day with sessions, empty days
+/// included.
+fn per_day_chart(out: &mut String, stats: &Stats) {
+    let days: Vec<(Date, u64)> = stats
+        .per_day
+        .iter()
+        .filter_map(|p| {
+            let day = Date::parse(
+                &p.period,
+                &time::format_description::well_known::Iso8601::DATE,
+            )
+            .ok()?;
+            Some((day, p.focus_secs / 60))
+        })
+        .collect();
+    let (Some(&(first, _)), Some(&(last, _))) = (days.first(), days.last()) else {
+        return;
+    };
+    let n = ((last - first).whole_days() + 1) as u32;
+    let max = days.iter().map(|(_, m)| *m).max().unwrap_or(0).max(1);
+    let (left, top, chart_h) = (36, 10, 160);
+    let slot = (WIDTH - left) as f64 / n as f64;
+    let height = top + chart_h + 30;
+    svg_open(out, height, "Focus minutes per day");
+    axis(out, left, top, chart_h, max);
+    // a label every few days so they do not overlap
+    let label_every = (n as f64 / (WIDTH as f64 / 70.0)).ceil().max(1.0) as u32;
+    for i in 0..n {
+        let day = first + Duration::days(i as i64);
+        let minutes = days.iter().find(|(d, _)| *d == day).map_or(0, |(_, m)| *m);
+        let x = left as f64 + i as f64 * slot;
+        let h = chart_h as f64 * minutes as f64 / max as f64;
+        let _ = writeln!(
+            out,
+            "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" fill=\"{}\">\
+             <title>{}: {} min</title></rect>",
+            x + slot * 0.1,
+            (top + chart_h) as f64 - h,
+            slot * 0.8,
+            h,
+            FOCUS_COLOR,
+            day,
+            minutes
+        );
+        if i % label_every == 0 {
+            let _ = writeln!(
+                out,
+                "<text x=\"{:.1}\" y=\"{}\" text-anchor=\"middle\">{:02}-{:02}</text>",
+                x + slot / 2.0,
+                top + chart_h + 16,
+                u8::from(day.month()),
+                day.day()
+            );
+        }
+    }
+    out.push_str("</svg>\n");
+}
+
+/// Weekday rows × hour columns, darker for more focus.
+fn heatmap(out: &mut String, grid: &[[u64; 24]; 7]) {
+    const WEEKDAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+    let (left, top, cell_w, cell_h) = (36, 4, 28, 20);
+    let max = grid.iter().flatten().copied().max().unwrap_or(0).max(1);
+    let height = top + 7 * cell_h + 20;
+    svg_open(out, height, "Focus by weekday and hour");
+    for (d, row) in grid.iter().enumerate() {
+        let y = top + d as u32 * cell_h;
+        let _ = writeln!(
+            out,
+            "<text x=\"0\" y=\"{}\">{}</text>",
+            y + cell_h - 6,
+            WEEKDAYS[d]
+        );
+        for (h, secs) in row.iter().enumerate() {
+            let opacity = if *secs == 0 {
+                0.05
+            } else {
+                0.15 + 0.85 * *secs as f64 / max as f64
+            };
+            let _ = writeln!(
+                out,
+                "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\" \
+                 fill-opacity=\"{:.2}\"><title>{} {:02}:00: {} min</title></rect>",
+                left + h as u32 * cell_w,
+                y,
+                cell_w - 2,
+                cell_h - 2,
+                FOCUS_COLOR,
+                opacity,
+                WEEKDAYS[d],
+                h,
+                secs / 60
+            );
+        }
+    }
+    for h in (0..24).step_by(3) {
+        let _ = writeln!(
+            out,
+            "<text x=\"{}\" y=\"{}\">{:02}h</text>",
+            left + h * cell_w,
+            top + 7 * cell_h + 14,
+            h
+        );
+    }
+    out.push_str("</svg>\n");
+}
+
+/// Horizontal bars, most focus first.
+fn per_task_chart(out: &mut String, stats: &Stats) {
+    let mut rows: Vec<(String, u64, usize)> = stats
+        .per_task
+        .iter()
+        .take(MAX_TASKS)
+        .map(|t| {
+            let name = t.task.clone().unwrap_or_else(|| "(no task)".to_string());
+            (name, t.focus_secs, t.sessions)
+        })
+        .collect();
+    let rest = &stats.per_task[stats.per_task.len().min(MAX_TASKS)..];
+    if !rest.is_empty() {
+        rows.push((
+            format!("{} other tasks", rest.len()),
+            rest.iter().map(|t| t.focus_secs).sum(),
+            rest.iter().map(|t| t.sessions).sum(),
+        ));
+    }
+    let (left, bar_h, gap) = (220, 18, 6);
+    let max = rows.iter().map(|r| r.1).max().unwrap_or(0).max(1);
+    let height = rows.len() as u32 * (bar_h + gap);
+    svg_open(out, height, "Focus per task");
+    for (i, (name, secs, sessions)) in rows.iter().enumerate() {
+        let y = i as u32 * (bar_h + gap);
+        let w = (WIDTH - left - 80) as f64 * *secs as f64 / max as f64;
+        let _ = writeln!(
+            out,
+            "<text x=\"0\" y=\"{}\">{}</text>\
+             <rect x=\"{}\" y=\"{}\" width=\"{:.1}\" height=\"{}\" fill=\"{}\">\
+             <title>{} sessions</title></rect>\
+             <text x=\"{:.1}\" y=\"{}\">{}</text>",
+            y + bar_h - 5,
+            escape(&truncate(name, 32)),
+            left,
+            y,
+            w,
+            bar_h,
+            FOCUS_COLOR,
+            sessions,
+            left as f64 + w + 6.0,
+            y + bar_h - 5,
+            hours_minutes(*secs)
+        );
+    }
+    out.push_str("</svg>\n");
+}
+
+/// One stacked bar: completed, interrupted, ongoing sessions.
+fn outcome_chart(out: &mut String, stats: &Stats) {
+    let parts = [
+        ("completed", stats.completed, FOCUS_COLOR),
+        ("interrupted", stats.interrupted, INTERRUPTED_COLOR),
+        ("ongoing", stats.ongoing, ONGOING_COLOR),
+    ];
+    let total = stats.sessions.max(1) as f64;
+    svg_open(out, 56, "Completed vs interrupted sessions");
+    let mut x = 0.0;
+    let mut legend_x = 0;
+    for (label, count, color) in parts {
+        let w = WIDTH as f64 * count as f64 / total;
+        let percent = 100.0 * count as f64 / total;
+        let _ = writeln!(
+            out,
+            "<rect x=\"{:.1}\" y=\"0\" width=\"{:.1}\" height=\"24\" fill=\"{}\">\
+             <title>{}: {} ({:.0}%)</title></rect>\
+             <rect x=\"{}\" y=\"36\" width=\"10\" height=\"10\" fill=\"{}\"/>\
+             <text x=\"{}\" y=\"46\">{} {} ({:.0}%)</text>",
+            x,
+            w,
+            color,
+            label,
+            count,
+            percent,
+            legend_x,
+            color,
+            legend_x + 14,
+            label,
+            count,
+            percent
+        );
+        x += w;
+        legend_x += 180;
+    }
+    out.push_str("</svg>\n");
+}
+
+fn svg_open(out: &mut String, height: u32, label: &str) {
+    let _ = writeln!(
+        out,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" \
+         viewBox=\"0 0 {w} {h}\" role=\"img\" aria-label=\"{label}\">",
+        w = WIDTH,
+        h = height,
+        label = label
+    );
+}
+
+/// Vertical scale of a bar chart: 0, half and max.
+fn axis(out: &mut String, left: u32, top: u32, height: u32, max: u64) {
+    for (value, y) in [(0, top + height), (max / 2, top + height / 2), (max, top)] {
+        let _ = writeln!(
+            out,
+            "<text x=\"{}\" y=\"{}\" text-anchor=\"end\">{}</text>\
+             <line x1=\"{}\" y1=\"{y}\" x2=\"{}\" y2=\"{y}\" stroke=\"#ddd\"/>",
+            left - 4,
+            y + 4,
+            value,
+            left,
+            WIDTH,
+            y = y
+        );
+    }
+}
+
+fn hours_minutes(secs: u64) -> String {
+    format!("{}h{:02}", secs / 3600, secs % 3600 / 60)
+}
+
+fn truncate(s: &str, max: usize) -> String {
+    if s.chars().count() <= max {
+        return s.to_string();
+    }
+    let mut t: String = s.chars().take(max - 1).collect();
+    t.push('…');
+    t
+}
+
+/// Text for HTML content and attributes.
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
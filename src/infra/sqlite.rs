@@ -0,0 +1,132 @@
+//! SQLite journal backend (`sqlite` feature): one row per session in
+//! `journal.db` in the data dir, replaced on every snapshot. Imports only
+//! replace a row with a snapshot updated later, so a stale import cannot undo
+//! progress made since. Indexed by start time, so range queries do not read
+//! the whole history.
+
+use crate::infra::storage::{JournalBackend, SessionEntry};
+use anyhow::{anyhow, Context, Result};
+use rusqlite::{params, Connection, OptionalExtension};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+use time::OffsetDateTime;
+
+/// File name of the database in the data dir.
+pub const DB_FILE: &str = "journal.db";
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS sessions (
+    id       TEXT PRIMARY KEY,
+    start_ns INTEGER NOT NULL,
+    last_updated_ns INTEGER NOT NULL,
+    state    TEXT NOT NULL,
+    task     TEXT,
+    entry    TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS sessions_start ON sessions (start_ns);
+";
+
+#[derive(Debug)]
+pub struct SqliteJournal {
+    pub path: PathBuf,
+    conn: Mutex<Connection>,
+}
+
+impl SqliteJournal {
+    /// Open (or create) the database at `path`.
+    pub fn open(path: &Path) -> Result<Self> {
+        let conn = Connection::open(path)
+            .with_context(|| format!("opening journal database {}", path.display()))?;
+        // several processes (CLI, GUI, daemon) may write
+        conn.busy_timeout(Duration::from_secs(5))?;
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+        conn.execute_batch(SCHEMA)
+            .context("creating journal tables")?;
+        Ok(SqliteJournal {
+            path: path.to_path_buf(),
+            conn: Mutex::new(conn),
+        })
+    }
+
+    /// Stored snapshot of session `id`.
+    pub fn get(&self, id: uuid::Uuid) -> Result<Option<SessionEntry>> {
+        let conn = self.lock()?;
+        let json: Option<String> = conn
+            .query_row(
+                "SELECT entry FROM sessions WHERE id = ?1",
+                params![id.to_string()],
+                |row| row.get(0),
+            )
+            .optional()?;
+        json.map(|j| serde_json::from_str(&j).context("parsing stored session"))
+            .transpose()
+    }
+
+    /// Insert or replace the row of `entry`'s session; with `if_newer`, an
+    /// existing row updated later is kept.
+    fn upsert(&self, entry: &SessionEntry, if_newer: bool) -> Result<()> {
+        let json = serde_json::to_string(entry)?;
+        let conn = self.lock()?;
+        conn.execute(
+            "INSERT INTO sessions (id, start_ns, last_updated_ns, state, task, entry)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+             ON CONFLICT (id) DO UPDATE SET
+                 start_ns = excluded.start_ns, last_updated_ns = excluded.last_updated_ns,
+                 state = excluded.state, task = excluded.task, entry = excluded.entry
+             WHERE NOT ?7 OR excluded.last_updated_ns > sessions.last_updated_ns",
+            params![
+                entry.id.to_string(),
+                nanos(entry.start),
+                nanos(entry.last_updated),
+                format!("{:?}", entry.state),
+                entry.cfg.task,
+                json,
+                if_newer
+            ],
+        )
+        .with_context(|| format!("saving session {}", entry.id))?;
+        Ok(())
+    }
+
+    fn lock(&self) -> Result<std::sync::MutexGuard<'_, Connection>> {
+        self.conn
+            .lock()
+            .map_err(|_| anyhow!("journal database lock poisoned"))
+    }
+}
+
+impl JournalBackend for SqliteJournal {
+    fn append(&self, entry: &SessionEntry) -> Result<()> {
+        self.upsert(entry, false)
+    }
+
+    fn update_session(&self, entry: &SessionEntry) -> Result<()> {
+        self.upsert(entry, true)
+    }
+
+    fn query_range(
+        &self,
+        from: Option<OffsetDateTime>,
+        until: Option<OffsetDateTime>,
+    ) -> Result<Vec<SessionEntry>> {
+        let conn = self.lock()?;
+        let mut stmt = conn.prepare(
+            "SELECT entry FROM sessions
+             WHERE (?1 IS NULL OR start_ns >= ?1) AND (?2 IS NULL OR start_ns < ?2)
+             ORDER BY start_ns",
+        )?;
+        let rows = stmt.query_map(params![from.map(nanos), until.map(nanos)], |row| {
+            row.get::<_, String>(0)
+        })?;
+        let mut out = Vec::new();
+        for json in rows {
+            out.push(serde_json::from_str(&json?).context("parsing stored session")?);
+        }
+        Ok(out)
+    }
+}
+
+fn nanos(t: OffsetDateTime) -> i64 {
+    t.unix_timestamp_nanos() as i64
+}
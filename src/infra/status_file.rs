@@ -0,0 +1,90 @@
+use crate::domain::clock::SharedClock;
+use crate::domain::events::{next_event, EventReceiver};
+use crate::domain::status::{RunState, SessionStatus};
+use anyhow::{Context, Result};
+use directories::ProjectDirs;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+use time::OffsetDateTime;
+use tracing::debug;
+
+/// While a session is active the file is rewritten at least this often, so a
+/// file older than `STALE_AFTER` means its writer died.
+const REFRESH_EVERY: Duration = Duration::from_secs(5);
+const STALE_AFTER: time::Duration = time::Duration::seconds(15);
+
+/// `status.json` in the data dir: the live `SessionStatus`, for shell prompts
+/// and status bars (`pomodoro-cli status`).
+#[derive(Debug, Clone)]
+pub struct StatusFile {
+    pub path: PathBuf,
+}
+
+impl StatusFile {
+    pub fn open_default() -> Result<Self> {
+        let pd = ProjectDirs::from("com", "you", "pomodoro").context("finding project dirs")?;
+        let data_dir = pd.data_dir();
+        fs::create_dir_all(data_dir).context("creating data dir")?;
+        Ok(StatusFile {
+            path: data_dir.join("status.json"),
+        })
+    }
+
+    /// Replace the file atomically (write + rename) so readers never see half a file.
+    pub fn write(&self, status: &SessionStatus) -> Result<()> {
+        let tmp = self.path.with_extension("json.tmp");
+        fs::write(&tmp, serde_json::to_string(status)?)
+            .with_context(|| format!("writing {}", tmp.display()))?;
+        fs::rename(&tmp, &self.path)
+            .with_context(|| format!("replacing {}", self.path.display()))?;
+        Ok(())
+    }
+
+    /// Current status; `Idle` when there is no file or its writer is gone.
+    pub fn read(&self, now: OffsetDateTime) -> Result<SessionStatus> {
+        if !self.path.exists() {
+            return Ok(SessionStatus::default());
+        }
+        let s = fs::read_to_string(&self.path)
+            .with_context(|| format!("reading {}", self.path.display()))?;
+        let status: SessionStatus = serde_json::from_str(&s).context("parsing status file")?;
+        let stale = status.updated_at.map_or(true, |at| now - at > STALE_AFTER);
+        if status.is_active() && stale {
+            debug!("status file is stale, reporting idle");
+            return Ok(SessionStatus::default());
+        }
+        Ok(status)
+    }
+
+    /// Status subscriber: rewrite the file on every event, and every few
+    /// seconds while paused. Returns after the last event.
+    pub async fn follow(self, mut rx: EventReceiver, clock: SharedClock) {
+        let mut status = SessionStatus::default();
+        loop {
+            let ev = tokio::select! {
+                ev = next_event(&mut rx) => ev,
+                // real time on purpose: a virtual clock must not advance while idle here
+                _ = tokio::time::sleep(REFRESH_EVERY), if status.state == RunState::Paused => {
+                    self.save(&mut status, &clock);
+                    continue;
+                }
+            };
+            let Some(ev) = ev else {
+                break;
+            };
+            status.apply(&ev);
+            self.save(&mut status, &clock);
+            if ev.is_last() {
+                break;
+            }
+        }
+    }
+
+    fn save(&self, status: &mut SessionStatus, clock: &SharedClock) {
+        status.updated_at = Some(clock.now());
+        if let Err(e) = self.write(status) {
+            debug!("status file not updated: {:#}", e);
+        }
+    }
+}
@@ -1,10 +1,22 @@
-use anyhow::{Context, Result};
+use crate::domain::clock::{Calendar, Clock, SharedClock, SystemClock};
+use crate::domain::events::{next_event, EventReceiver, SessionEvent};
+use crate::domain::schedule::{Segment, SegmentKind};
+use crate::infra::export::{self, ExportFormat, Output};
+use anyhow::{bail, Context, Result};
 use directories::ProjectDirs;
-use serde::{Deserialize, Serialize};
-use std::fs::{self, OpenOptions};
-use std::io::Write;
-use std::path::PathBuf;
-use time::OffsetDateTime;
+use fs4::fs_std::FileExt;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+use time::format_description::well_known::{Iso8601, Rfc3339};
+use time::{Date, OffsetDateTime};
+use tracing::{debug, error, info, warn};
+use uuid::Uuid;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum SessionState {
@@ -13,8 +25,160 @@ pub enum SessionState {
     Interrupted,
 }
 
+impl FromStr for SessionState {
+    type Err = anyhow::Error;
+
+    /// Case-insensitive state name, e.g. `completed`.
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "ongoing" => Ok(SessionState::Ongoing),
+            "completed" => Ok(SessionState::Completed),
+            "interrupted" => Ok(SessionState::Interrupted),
+            _ => bail!(
+                "unknown session state {:?} (ongoing, completed, interrupted)",
+                s
+            ),
+        }
+    }
+}
+
+/// One pause taken during a session. `end` stays `None` while the pause is running.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PauseRecord {
+    #[serde(with = "time::serde::rfc3339")]
+    pub start: OffsetDateTime,
+
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub end: Option<OffsetDateTime>,
+
+    /// paused duration in seconds (0 until the pause ends)
+    pub seconds: u64,
+}
+
+/// The wall clock moved differently from the monotonic clock while a segment
+/// was running. Positive `seconds`: the wall clock ran ahead (suspend/resume or
+/// clock set forward), credited to the running segment. Negative: the clock was
+/// set back, which does not affect timing.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ClockGap {
+    #[serde(with = "time::serde::rfc3339")]
+    pub at: OffsetDateTime,
+    pub seconds: i64,
+}
+
+/// What actually happened for one segment of the schedule.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SegmentRecord {
+    pub kind: SegmentKind,
+    pub cycle_index: u8,
+    pub planned_secs: u64,
+    /// running time in seconds, pauses excluded
+    pub actual_secs: u64,
+
+    /// `None` for records loaded from the legacy `"FOCUS:1500s"` format
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub start: Option<OffsetDateTime>,
+
+    /// `None` while the segment is running (and for legacy records)
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub end: Option<OffsetDateTime>,
+
+    /// ended early on request (skip), the session went on with the next segment
+    #[serde(default)]
+    pub skipped: bool,
+
+    /// custom label from the preset's segment list
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+}
+
+impl SegmentRecord {
+    /// Parse the legacy `"LABEL:<secs>s"` journal format (assumed completed).
+    fn from_legacy(s: &str, cycle_index: u8) -> Option<Self> {
+        let (label, secs) = s.rsplit_once(':')?;
+        let kind = SegmentKind::from_label(label)?;
+        let secs: u64 = secs.strip_suffix('s')?.parse().ok()?;
+        Some(Self {
+            kind,
+            cycle_index,
+            planned_secs: secs,
+            actual_secs: secs,
+            start: None,
+            end: None,
+            skipped: false,
+            label: None,
+        })
+    }
+
+    /// Custom label, or the kind's label.
+    pub fn label(&self) -> &str {
+        self.label.as_deref().unwrap_or(self.kind.label())
+    }
+
+    /// The segment ran for its whole planned duration (open-ended ones: it
+    /// was ended).
+    pub fn is_complete(&self) -> bool {
+        if self.kind.is_open_ended() {
+            return self.end.is_some();
+        }
+        self.actual_secs >= self.planned_secs
+    }
+
+    /// `actual/planned s`, or just the actual length for open-ended segments.
+    pub fn duration_text(&self) -> String {
+        if self.kind.is_open_ended() {
+            format!("{}s", self.actual_secs)
+        } else {
+            format!("{}/{}s", self.actual_secs, self.planned_secs)
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SegmentRepr {
+    Record(SegmentRecord),
+    Legacy(String),
+}
+
+/// Accept both typed records and the legacy string list; legacy entries get
+/// their `cycle_index` from the focus segments seen so far.
+fn deserialize_segments<'de, D>(deserializer: D) -> Result<Vec<SegmentRecord>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let raw = Vec::<SegmentRepr>::deserialize(deserializer)?;
+    let mut out = Vec::with_capacity(raw.len());
+    let mut cycle = 0u8;
+    for r in raw {
+        match r {
+            SegmentRepr::Record(rec) => {
+                cycle = rec.cycle_index;
+                out.push(rec);
+            }
+            SegmentRepr::Legacy(s) => {
+                if s.starts_with("FOCUS:") {
+                    cycle = cycle.saturating_add(1);
+                }
+                let rec = SegmentRecord::from_legacy(&s, cycle.max(1)).ok_or_else(|| {
+                    serde::de::Error::custom(format!("invalid legacy segment {:?}", s))
+                })?;
+                out.push(rec);
+            }
+        }
+    }
+    Ok(out)
+}
+
+/// One session. The journal gets a new snapshot of it after every segment;
+/// the last line with its `id` is the current state.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SessionEntry {
+    /// nil in journals written before IDs existed; `Journal` readers then
+    /// derive one from `start` (see `legacy_id`)
+    #[serde(default)]
+    pub id: Uuid,
+
     #[serde(with = "time::serde::rfc3339")]
     pub start: OffsetDateTime,
 
@@ -23,98 +187,804 @@ pub struct SessionEntry {
 
     pub cfg: crate::domain::config::Config,
     pub state: SessionState,
-    pub segments: Vec<String>,
+    #[serde(deserialize_with = "deserialize_segments")]
+    pub segments: Vec<SegmentRecord>,
 
     #[serde(with = "time::serde::rfc3339")]
     pub last_updated: OffsetDateTime,
+
+    /// pauses taken during the session (absent in older journals)
+    #[serde(default)]
+    pub pauses: Vec<PauseRecord>,
+
+    /// total paused time in seconds, i.e. wall-clock time minus real focus/break time
+    #[serde(default)]
+    pub paused_secs: u64,
+
+    /// suspends and clock jumps detected while running (absent in older journals)
+    #[serde(default)]
+    pub clock_gaps: Vec<ClockGap>,
 }
 
 impl SessionEntry {
     pub fn new(cfg: &crate::domain::config::Config) -> anyhow::Result<Self> {
+        Self::new_with_clock(cfg, &SystemClock)
+    }
+
+    /// Same as `new`, timestamped by `clock`.
+    pub fn new_with_clock(
+        cfg: &crate::domain::config::Config,
+        clock: &dyn Clock,
+    ) -> anyhow::Result<Self> {
+        let now = clock.now();
         Ok(Self {
-            start: OffsetDateTime::now_utc(),
+            id: Uuid::new_v4(),
+            start: now,
             end: None,
             cfg: cfg.clone(),
             state: SessionState::Ongoing,
             segments: vec![],
-            last_updated: OffsetDateTime::now_utc(),
+            last_updated: now,
+            pauses: vec![],
+            paused_secs: 0,
+            clock_gaps: vec![],
         })
     }
 
-    pub fn append_to_path(&self, path: &PathBuf) -> Result<()> {
+    pub fn is_paused(&self) -> bool {
+        self.pauses.last().is_some_and(|p| p.end.is_none())
+    }
+
+    /// Open a pause at `at`. Returns `false` if a pause is already running.
+    pub fn begin_pause(&mut self, at: OffsetDateTime) -> bool {
+        if self.is_paused() {
+            return false;
+        }
+        self.pauses.push(PauseRecord {
+            start: at,
+            end: None,
+            seconds: 0,
+        });
+        self.last_updated = at;
+        true
+    }
+
+    /// Open a record for `seg` starting at `at`.
+    pub fn start_segment(&mut self, seg: &Segment, at: OffsetDateTime) {
+        self.segments.push(SegmentRecord {
+            kind: seg.kind,
+            cycle_index: seg.cycle_index,
+            planned_secs: seg.seconds,
+            actual_secs: 0,
+            start: Some(at),
+            end: None,
+            skipped: false,
+            label: seg.label.clone(),
+        });
+        self.last_updated = at;
+    }
+
+    /// Close the running segment after it counted down its full duration.
+    pub fn finish_segment(&mut self, at: OffsetDateTime) {
+        if let Some(rec) = self.segments.last_mut().filter(|r| r.end.is_none()) {
+            rec.actual_secs = rec.planned_secs;
+            rec.end = Some(at);
+        }
+        self.last_updated = at;
+    }
+
+    /// Close the running open-ended segment after `running_secs` (flow ended).
+    pub fn end_open_segment(&mut self, at: OffsetDateTime, running_secs: u64) {
+        if let Some(rec) = self.segments.last_mut().filter(|r| r.end.is_none()) {
+            rec.actual_secs = running_secs;
+            rec.end = Some(at);
+        }
+        self.last_updated = at;
+    }
+
+    /// Close the running segment early (interrupt). `running_secs` is the time
+    /// measured by the engine; when unknown (crash) it is the elapsed wall time
+    /// minus the pauses taken since the segment started.
+    pub fn cut_segment(&mut self, at: OffsetDateTime, running_secs: Option<u64>) {
+        if let Some(rec) = self.segments.last_mut().filter(|r| r.end.is_none()) {
+            let estimated = rec.start.map(|start| {
+                let elapsed = (at - start).whole_seconds().max(0) as u64;
+                let paused: u64 = self
+                    .pauses
+                    .iter()
+                    .filter(|p| p.start >= start)
+                    .map(|p| p.seconds)
+                    .sum();
+                elapsed.saturating_sub(paused)
+            });
+            if let Some(secs) = running_secs.or(estimated) {
+                rec.actual_secs = if rec.kind.is_open_ended() {
+                    secs
+                } else {
+                    secs.min(rec.planned_secs)
+                };
+            }
+            rec.end = Some(at);
+        }
+        self.last_updated = at;
+    }
+
+    /// End the running segment early on request and move on (skip).
+    pub fn skip_segment(&mut self, at: OffsetDateTime, running_secs: u64) {
+        if !self.segments.last().is_some_and(|r| r.end.is_none()) {
+            return;
+        }
+        self.cut_segment(at, Some(running_secs));
+        if let Some(rec) = self.segments.last_mut() {
+            rec.skipped = true;
+        }
+    }
+
+    /// Number of schedule segments that ran to completion or were skipped,
+    /// i.e. the index of the segment a resumed session restarts from.
+    pub fn completed_segments(&self) -> usize {
+        self.segments
+            .iter()
+            .filter(|r| r.is_complete() || r.skipped)
+            .count()
+    }
+
+    /// e.g. `3/8 segments done` (`3 segments done` for endless sessions).
+    pub fn progress(&self) -> String {
+        let done = self.completed_segments();
+        match self.cfg.clone().into_schedule().total() {
+            Some(total) => format!("{}/{} segments done", done, total),
+            None => format!("{} segments done", done),
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.state == SessionState::Completed
+    }
+
+    /// `#words` of the task label, without the `#` (`"Review #work #rust"`
+    /// has tags `work` and `rust`).
+    pub fn tags(&self) -> Vec<&str> {
+        self.cfg
+            .task
+            .as_deref()
+            .unwrap_or("")
+            .split_whitespace()
+            .filter_map(|w| w.strip_prefix('#'))
+            .filter(|t| !t.is_empty())
+            .collect()
+    }
+
+    /// Reopen an interrupted/crashed entry at `at`. The time spent away is
+    /// recorded as a pause and a cut-short segment is kept as is.
+    pub fn reopen(&mut self, at: OffsetDateTime) {
+        let away_since = self.end.take().unwrap_or(self.last_updated);
+        self.end_pause(away_since);
+        self.cut_segment(away_since, None);
+        self.begin_pause(away_since);
+        self.end_pause(at);
+        self.state = SessionState::Ongoing;
+        self.last_updated = at;
+    }
+
+    /// Focus time actually spent, pauses excluded.
+    pub fn focus_secs(&self) -> u64 {
+        self.segments
+            .iter()
+            .filter(|r| r.kind.is_focus())
+            .map(|r| r.actual_secs)
+            .sum()
+    }
+
+    /// Focus time the schedule planned: the whole schedule when it is finite,
+    /// the focus segments that ran for endless and flowtime sessions.
+    pub fn planned_focus_secs(&self) -> u64 {
+        let schedule = self.cfg.clone().into_schedule();
+        if schedule.total().is_some() {
+            return schedule
+                .segments
+                .iter()
+                .filter(|s| s.kind.is_focus())
+                .map(|s| s.seconds)
+                .sum();
+        }
+        self.segments
+            .iter()
+            .filter(|r| r.kind.is_focus())
+            .map(|r| r.planned_secs)
+            .sum()
+    }
+
+    /// Focus segments that ran to their end (not skipped or cut short).
+    pub fn completed_focus(&self) -> usize {
+        self.segments
+            .iter()
+            .filter(|r| r.kind.is_focus() && r.is_complete() && !r.skipped)
+            .count()
+    }
+
+    /// Pauses taken, plus one when the session was stopped before its end.
+    pub fn interruptions(&self) -> usize {
+        self.pauses.len() + usize::from(self.state == SessionState::Interrupted)
+    }
+
+    /// Close the running pause at `at` and return its length in seconds.
+    pub fn end_pause(&mut self, at: OffsetDateTime) -> Option<u64> {
+        let pause = self.pauses.last_mut().filter(|p| p.end.is_none())?;
+        let secs = (at - pause.start).whole_seconds().max(0) as u64;
+        pause.end = Some(at);
+        pause.seconds = secs;
+        self.paused_secs += secs;
+        self.last_updated = at;
+        Some(secs)
+    }
+
+    /// Append this snapshot as one JSON line, under the journal lock, and
+    /// flush it to disk before returning. A torn last line (crash while
+    /// writing) is closed first so the new line stays readable.
+    pub fn append_to_path(&self, path: &Path) -> Result<()> {
+        let mut line = serde_json::to_string(self)?;
+        line.push('\n');
+        let _lock = JournalLock::exclusive(path)?;
         let mut f = OpenOptions::new()
             .create(true)
+            .read(true)
             .append(true)
             .open(path)
             .with_context(|| format!("open journal {}", path.display()))?;
-        let s = serde_json::to_string(self)?;
-        writeln!(f, "{}", s)?;
+        if f.metadata()?.len() > 0 {
+            let mut last = [0u8; 1];
+            f.seek(SeekFrom::End(-1))?;
+            f.read_exact(&mut last)?;
+            if last[0] != b'\n' {
+                warn!("closing torn last line of {}", path.display());
+                line.insert(0, '\n');
+            }
+        }
+        f.write_all(line.as_bytes())
+            .with_context(|| format!("appending to {}", path.display()))?;
+        f.sync_data()
+            .with_context(|| format!("syncing {}", path.display()))?;
         Ok(())
     }
+}
 
-    pub fn save_to_path(&self, path: &PathBuf) -> Result<()> {
-        let s = serde_json::to_string_pretty(self)?;
-        fs::write(path, s)?;
-        Ok(())
+/// Advisory lock shared by every journal file of a directory (a
+/// `.journal.lock` file next to them): exclusive while appending or
+/// compacting, shared while reading, so a CLI, a GUI and a daemon can use the
+/// same journal. Released on drop.
+struct JournalLock {
+    _file: File,
+}
+
+impl JournalLock {
+    fn open(journal: &Path) -> Result<File> {
+        let dir = journal.parent().unwrap_or(Path::new("."));
+        let path = dir.join(".journal.lock");
+        OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&path)
+            .with_context(|| format!("opening lock file {}", path.display()))
+    }
+
+    fn exclusive(journal: &Path) -> Result<Self> {
+        let f = Self::open(journal)?;
+        FileExt::lock_exclusive(&f).context("locking journal")?;
+        Ok(Self { _file: f })
+    }
+
+    fn shared(journal: &Path) -> Result<Self> {
+        let f = Self::open(journal)?;
+        // fs4's, not std's inherent `File::lock_shared` (Rust 1.89)
+        FileExt::lock_shared(&f).context("locking journal")?;
+        Ok(Self { _file: f })
     }
 }
 
-pub struct Journal {
+/// What `JsonlJournal::compact` rewrote.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CompactReport {
+    pub files: usize,
+    pub lines_before: usize,
+    pub lines_after: usize,
+    /// lines that did not parse, kept unchanged
+    pub unparsed: usize,
+}
+
+/// Filter for `Journal::query`; unset fields match everything.
+#[derive(Debug, Clone, Default)]
+pub struct JournalQuery {
+    /// first day, inclusive (journal day the session started on)
+    pub from: Option<Date>,
+    /// last day, inclusive
+    pub to: Option<Date>,
+    /// case-insensitive substring of the task
+    pub task: Option<String>,
+    /// one of the session's tags, with or without `#`
+    pub tag: Option<String>,
+    pub state: Option<SessionState>,
+}
+
+impl JournalQuery {
+    /// Whether `e` passes the filter, its days taken in `calendar`.
+    pub fn matches(&self, e: &SessionEntry, calendar: &Calendar) -> bool {
+        let day = calendar.day_of(e.start);
+        if self.from.is_some_and(|from| day < from) || self.to.is_some_and(|to| day > to) {
+            return false;
+        }
+        if let Some(needle) = &self.task {
+            let task = e.cfg.task.as_deref().unwrap_or("").to_lowercase();
+            if !task.contains(&needle.to_lowercase()) {
+                return false;
+            }
+        }
+        if let Some(tag) = &self.tag {
+            let tag = tag.trim_start_matches('#');
+            if !e.tags().iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+                return false;
+            }
+        }
+        self.state.as_ref().map_or(true, |s| &e.state == s)
+    }
+}
+
+/// Where journaled sessions are kept. Every save is a full snapshot of a
+/// session; the latest snapshot per `id` is the session's state.
+pub trait JournalBackend: fmt::Debug + Send + Sync {
+    /// Store a new snapshot of `entry`'s session.
+    fn append(&self, entry: &SessionEntry) -> Result<()>;
+
+    /// Replace the stored state of `entry`'s session (imports, edits), unless
+    /// the stored one was updated later (`last_updated`). By default a new
+    /// snapshot, which supersedes the older ones.
+    fn update_session(&self, entry: &SessionEntry) -> Result<()> {
+        self.append(entry)
+    }
+
+    /// Sessions started at or after `from` and before `until` (unbounded
+    /// when `None`), latest snapshot of each, ordered by start.
+    fn query_range(
+        &self,
+        from: Option<OffsetDateTime>,
+        until: Option<OffsetDateTime>,
+    ) -> Result<Vec<SessionEntry>>;
+}
+
+/// The default backend: one `journal-YYYY-MM-DD.jsonl` file per journal day
+/// in the data dir, written to `path` (the file of the day it was opened).
+#[derive(Debug, Clone)]
+pub struct JsonlJournal {
     pub dir: PathBuf,
     pub path: PathBuf,
 }
 
-impl Journal {
-    pub fn open_default() -> Result<Self> {
+impl JsonlJournal {
+    /// Journal of the data dir, writing to the file of journal day `today`.
+    pub fn open_default(today: Date) -> Result<Self> {
         let pd = ProjectDirs::from("com", "you", "pomodoro").context("finding project dirs")?;
         let data_dir = pd.data_dir();
         fs::create_dir_all(data_dir).context("creating data dir")?;
-        let today = OffsetDateTime::now_utc().date();
         let file_name = format!("journal-{}.jsonl", today);
         let path = data_dir.join(file_name);
-        Ok(Journal {
+        Ok(JsonlJournal {
             dir: data_dir.to_path_buf(),
             path,
         })
     }
 
+    /// All journal lines of `path`, in write order. Lines that fail to
+    /// parse are skipped with a warning.
+    pub fn read_entries(&self) -> Result<Vec<SessionEntry>> {
+        read_file(&self.path)
+    }
+
+    /// Journal files of the data dir (`journal-YYYY-MM-DD.jsonl`) by day.
+    pub fn day_files(&self) -> Result<Vec<(Date, PathBuf)>> {
+        let mut files = Vec::new();
+        if !self.dir.is_dir() {
+            return Ok(files);
+        }
+        for entry in fs::read_dir(&self.dir).context("reading data dir")? {
+            let path = entry?.path();
+            let Some(day) = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .and_then(|n| n.strip_prefix("journal-"))
+                .and_then(|n| n.strip_suffix(".jsonl"))
+                .and_then(|d| Date::parse(d, &Iso8601::DATE).ok())
+            else {
+                continue;
+            };
+            files.push((day, path));
+        }
+        files.sort();
+        Ok(files)
+    }
+
+    /// Sessions of every day file (and of `path`, if it is not one of
+    /// them), latest snapshot of each, ordered by start.
+    pub fn read_all(&self) -> Result<Vec<SessionEntry>> {
+        self.query_range(None, None)
+    }
+
+    /// Rewrite the day files before `before` to one line per session, with
+    /// the IDs of legacy lines written out. Lines that do not parse are kept
+    /// as they are, after the sessions. Each file is replaced atomically
+    /// (synced temp file + rename) under the journal lock.
+    pub fn compact(&self, before: Date) -> Result<CompactReport> {
+        let mut report = CompactReport::default();
+        for (day, path) in self.day_files()? {
+            if day >= before {
+                continue;
+            }
+            let _lock = JournalLock::exclusive(&path)?;
+            let file = ParsedFile::read(&path)?;
+            let sessions = latest_per_session(file.entries.clone());
+            let lines_after = sessions.len() + file.unparsed.len();
+            if lines_after == file.lines() && file.legacy == 0 {
+                continue;
+            }
+            let mut body = Vec::new();
+            for e in &sessions {
+                serde_json::to_writer(&mut body, e)?;
+                body.push(b'\n');
+            }
+            for line in &file.unparsed {
+                body.extend_from_slice(line);
+                body.push(b'\n');
+            }
+            let tmp = path.with_extension("jsonl.tmp");
+            let mut f =
+                File::create(&tmp).with_context(|| format!("creating {}", tmp.display()))?;
+            f.write_all(&body)
+                .and_then(|()| f.sync_all())
+                .with_context(|| format!("writing {}", tmp.display()))?;
+            fs::rename(&tmp, &path).with_context(|| format!("replacing {}", path.display()))?;
+            // make the rename itself durable (not supported everywhere)
+            if let Err(e) = File::open(&self.dir).and_then(|d| d.sync_all()) {
+                debug!("could not sync {}: {}", self.dir.display(), e);
+            }
+            report.files += 1;
+            report.lines_before += file.lines();
+            report.lines_after += lines_after;
+            report.unparsed += file.unparsed.len();
+        }
+        Ok(report)
+    }
+}
+
+impl JournalBackend for JsonlJournal {
+    fn append(&self, entry: &SessionEntry) -> Result<()> {
+        entry.append_to_path(&self.path)
+    }
+
+    fn query_range(
+        &self,
+        from: Option<OffsetDateTime>,
+        until: Option<OffsetDateTime>,
+    ) -> Result<Vec<SessionEntry>> {
+        // a session is journaled in the file of the day it started or, once
+        // resumed, of a later day; a file named more than two days before
+        // `from` (offset and day start hour included) cannot hold it
+        let oldest = from.map(|f| f.date() - time::Duration::days(2));
+        let mut paths: Vec<PathBuf> = self
+            .day_files()?
+            .into_iter()
+            .filter(|(day, _)| oldest.map_or(true, |o| *day >= o))
+            .map(|(_, p)| p)
+            .collect();
+        if !paths.contains(&self.path) {
+            paths.push(self.path.clone());
+        }
+        let mut entries = Vec::new();
+        for path in paths {
+            entries.extend(read_file(&path)?);
+        }
+        let mut entries = latest_per_session(entries);
+        entries
+            .retain(|e| from.map_or(true, |f| e.start >= f) && until.map_or(true, |u| e.start < u));
+        Ok(entries)
+    }
+}
+
+/// The session journal, over a `JournalBackend`. `dir` is where exports are
+/// written; days and displayed times follow `calendar` (UTC by default) and
+/// "today" is read from `clock` (`SystemClock` by default).
+#[derive(Clone)]
+pub struct Journal {
+    pub dir: PathBuf,
+    backend: Arc<dyn JournalBackend>,
+    calendar: Calendar,
+    clock: SharedClock,
+}
+
+impl fmt::Debug for Journal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Journal")
+            .field("dir", &self.dir)
+            .field("backend", &self.backend)
+            .field("calendar", &self.calendar)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Journal {
+    /// The journal of the data dir: SQLite when `journal.db` exists there (and
+    /// the `sqlite` feature is built, see `pomodoro-cli import`), JSONL files
+    /// otherwise. Days follow `calendar`.
+    pub fn open_default(calendar: Calendar) -> Result<Self> {
+        Self::open_default_with_clock(calendar, Arc::new(SystemClock))
+    }
+
+    /// Like `open_default`, with today's file and day taken from `clock`.
+    pub fn open_default_with_clock(calendar: Calendar, clock: SharedClock) -> Result<Self> {
+        let jsonl = JsonlJournal::open_default(calendar.day_of(clock.now()))?;
+        #[cfg(feature = "sqlite")]
+        {
+            let db = jsonl.dir.join(crate::infra::sqlite::DB_FILE);
+            if db.exists() {
+                let backend = crate::infra::sqlite::SqliteJournal::open(&db)?;
+                return Ok(Journal::with_backend(jsonl.dir, Arc::new(backend))
+                    .with_calendar(calendar)
+                    .with_clock(clock));
+            }
+        }
+        Ok(Journal::jsonl(jsonl.dir, jsonl.path)
+            .with_calendar(calendar)
+            .with_clock(clock))
+    }
+
+    /// JSONL journal in `dir`, writing to `path`.
+    pub fn jsonl(dir: PathBuf, path: PathBuf) -> Self {
+        let backend = JsonlJournal {
+            dir: dir.clone(),
+            path,
+        };
+        Journal::with_backend(dir, Arc::new(backend))
+    }
+
+    pub fn with_backend(dir: PathBuf, backend: Arc<dyn JournalBackend>) -> Self {
+        Journal {
+            dir,
+            backend,
+            calendar: Calendar::default(),
+            clock: Arc::new(SystemClock),
+        }
+    }
+
+    pub fn with_calendar(mut self, calendar: Calendar) -> Self {
+        self.calendar = calendar;
+        self
+    }
+
+    /// Take "today" from `clock` (e.g. a `VirtualClock` in tests).
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn calendar(&self) -> &Calendar {
+        &self.calendar
+    }
+
+    pub fn backend(&self) -> &dyn JournalBackend {
+        self.backend.as_ref()
+    }
+
     pub fn append(&self, entry: &SessionEntry) -> Result<()> {
-        entry.append_to_path(&self.path)?;
+        self.backend.append(entry)
+    }
+
+    /// Journal subscriber: append a snapshot after each completed segment and
+    /// when the session ends. Returns after the last event.
+    pub async fn record_events(self, mut rx: EventReceiver) {
+        while let Some(ev) = next_event(&mut rx).await {
+            let entry = match &ev {
+                SessionEvent::SegmentCompleted { entry, .. }
+                | SessionEvent::Interrupted { entry }
+                | SessionEvent::Finished { entry } => Some(entry),
+                _ => None,
+            };
+            if let Some(entry) = entry {
+                match self.append(entry) {
+                    Ok(()) if ev.is_last() => info!("Session saved to journal"),
+                    Ok(()) => {}
+                    Err(err) => error!("Failed to append session to journal: {:?}", err),
+                }
+            }
+            if ev.is_last() {
+                break;
+            }
+        }
+    }
+
+    /// Sessions started today (journal day of the calendar), latest snapshot
+    /// of each, ordered by start.
+    pub fn sessions(&self) -> Result<Vec<SessionEntry>> {
+        let today = self.today();
+        self.query(&JournalQuery {
+            from: Some(today),
+            to: Some(today),
+            ..JournalQuery::default()
+        })
+    }
+
+    /// Sessions of all days, latest snapshot of each, ordered by start.
+    pub fn read_all(&self) -> Result<Vec<SessionEntry>> {
+        self.backend.query_range(None, None)
+    }
+
+    /// Sessions of all days matching `query`, ordered by start.
+    pub fn query(&self, query: &JournalQuery) -> Result<Vec<SessionEntry>> {
+        // a session's day follows its own offset, which may differ from the
+        // calendar's (DST, travel): ask for a day more on each side
+        let margin = time::Duration::days(1);
+        let mut entries = self.backend.query_range(
+            query.from.map(|d| self.calendar.day_start(d) - margin),
+            query.to.map(|d| self.calendar.day_end(d) + margin),
+        )?;
+        entries.retain(|e| query.matches(e, &self.calendar));
+        Ok(entries)
+    }
+
+    /// Copy every session of `from` into this journal; returns how many.
+    /// A session already there is only replaced by a newer snapshot (see
+    /// `update_session`), so importing twice is harmless.
+    pub fn import(&self, from: &dyn JournalBackend) -> Result<usize> {
+        let sessions = from.query_range(None, None)?;
+        for e in &sessions {
+            self.backend.update_session(e)?;
+        }
+        Ok(sessions.len())
+    }
+
+    /// Latest snapshot of the most recently updated session of today or
+    /// yesterday, if it never completed (crash, power loss, Ctrl-C). A
+    /// session interrupted just before the day changed is still found.
+    pub fn latest_unfinished(&self) -> Result<Option<SessionEntry>> {
+        let yesterday = self.today() - time::Duration::days(1);
+        let latest = self
+            .backend
+            .query_range(Some(self.calendar.day_start(yesterday)), None)?
+            .into_iter()
+            .max_by_key(|e| e.last_updated);
+        Ok(latest.filter(|e| !e.is_finished()))
+    }
+
+    /// Today's journal day in the calendar.
+    pub fn today(&self) -> Date {
+        self.calendar.day_of(self.clock.now())
+    }
+
+    /// Export the sessions matching `query` to `output`; returns how many.
+    pub fn export(
+        &self,
+        format: ExportFormat,
+        query: &JournalQuery,
+        output: &Output,
+    ) -> Result<usize> {
+        let entries = self.query(query)?;
+        let today = self.today();
+        let title = export::period(query, today);
+        output.write(&format.render(&title, &entries, &self.calendar, today))?;
+        Ok(entries.len())
+    }
+
+    /// Today's sessions to `journal-today.<ext>` in `dir`.
+    fn export_today(&self, format: ExportFormat) -> Result<()> {
+        let today = self.today();
+        let today = JournalQuery {
+            from: Some(today),
+            to: Some(today),
+            ..JournalQuery::default()
+        };
+        let out = self
+            .dir
+            .join(format!("journal-today.{}", format.extension()));
+        self.export(format, &today, &Output::File(out))?;
         Ok(())
     }
 
     pub fn export_markdown_today(&self) -> Result<()> {
-        let content = fs::read_to_string(&self.path).with_context(|| "reading journal file")?;
-        let mut md = String::new();
-        md.push_str("# Pomodoro journal (today)\n\n");
-        for line in content.lines() {
-            let e: SessionEntry = serde_json::from_str(line)?;
-            md.push_str(&format!(
-                "- **start**: {}\n  - task: {:?}\n  - state: {:?}\n  - segments: {:?}\n\n",
-                e.start, e.cfg.task, e.state, e.segments
-            ));
-        }
-        let out = self.dir.join("journal-today.md");
-        fs::write(&out, md)?;
-        Ok(())
+        self.export_today(ExportFormat::Markdown)
     }
 
     pub fn export_csv_today(&self) -> Result<()> {
-        let content = fs::read_to_string(&self.path).with_context(|| "reading journal file")?;
-        let mut csv = String::from("start,end,state,task,segments\n");
-        for line in content.lines() {
-            let e: SessionEntry = serde_json::from_str(line)?;
-            let end = e.end.map(|d| d.to_string()).unwrap_or_default();
-            let task = e.cfg.task.clone().unwrap_or_default().replace(',', " ");
-            let segments = e.segments.join(" | ");
-            // corrigé : éviter `format!(..., format!(..))`
-            csv.push_str(&format!(
-                "{},{:?},{:?},{},{}\n",
-                e.start, end, e.state, task, segments
-            ));
-        }
-        let out = self.dir.join("journal-today.csv");
-        fs::write(&out, csv)?;
-        Ok(())
+        self.export_today(ExportFormat::Csv)
+    }
+
+    /// Today's focus segments as calendar events.
+    pub fn export_ics_today(&self) -> Result<()> {
+        self.export_today(ExportFormat::Ics)
+    }
+}
+
+/// RFC 3339 with the offset of `t`, e.g. `2024-05-02T09:00:00+02:00`.
+pub fn timestamp(t: OffsetDateTime) -> String {
+    t.format(&Rfc3339).unwrap_or_else(|_| t.to_string())
+}
+
+/// Entries of one journal file, in write order; unparsable lines are skipped
+/// with a warning.
+fn read_file(path: &Path) -> Result<Vec<SessionEntry>> {
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+    let _lock = JournalLock::shared(path)?;
+    parse_file(path)
+}
+
+/// `read_file` for callers that hold the journal lock already.
+fn parse_file(path: &Path) -> Result<Vec<SessionEntry>> {
+    Ok(ParsedFile::read(path)?.entries)
+}
+
+/// Every non-empty line of a journal file, parsed or not.
+struct ParsedFile {
+    entries: Vec<SessionEntry>,
+    /// lines that did not parse (torn writes, newer schema), verbatim
+    unparsed: Vec<Vec<u8>>,
+    /// parsed lines without an `id`, given a `legacy_id`
+    legacy: usize,
+}
+
+impl ParsedFile {
+    /// Read as bytes: a line torn inside a multi-byte character is not
+    /// UTF-8 and is skipped like any other line that does not parse.
+    fn read(path: &Path) -> Result<Self> {
+        let content =
+            fs::read(path).with_context(|| format!("reading journal file {}", path.display()))?;
+        let mut file = ParsedFile {
+            entries: Vec::new(),
+            unparsed: Vec::new(),
+            legacy: 0,
+        };
+        for (i, raw) in content.split(|b| *b == b'\n').enumerate() {
+            let raw = raw.strip_suffix(b"\r").unwrap_or(raw);
+            if raw.iter().all(u8::is_ascii_whitespace) {
+                continue;
+            }
+            let parsed = std::str::from_utf8(raw)
+                .map_err(|e| e.to_string())
+                .and_then(|line| {
+                    serde_json::from_str::<SessionEntry>(line).map_err(|e| e.to_string())
+                });
+            match parsed {
+                Ok(mut e) => {
+                    if e.id.is_nil() {
+                        e.id = legacy_id(e.start);
+                        file.legacy += 1;
+                    }
+                    file.entries.push(e)
+                }
+                Err(err) => {
+                    warn!("skipping {} line {}: {}", path.display(), i + 1, err);
+                    file.unparsed.push(raw.to_vec());
+                }
+            }
+        }
+        Ok(file)
+    }
+
+    fn lines(&self) -> usize {
+        self.entries.len() + self.unparsed.len()
+    }
+}
+
+/// Stable ID for a session journaled without one: its start instant.
+pub fn legacy_id(start: OffsetDateTime) -> Uuid {
+    Uuid::from_u128(start.unix_timestamp_nanos() as u128)
+}
+
+/// Last snapshot of each session in `entries` (write order), ordered by start.
+fn latest_per_session(entries: Vec<SessionEntry>) -> Vec<SessionEntry> {
+    let mut latest: HashMap<Uuid, SessionEntry> = HashMap::new();
+    for e in entries {
+        latest.insert(e.id, e);
     }
+    let mut out: Vec<SessionEntry> = latest.into_values().collect();
+    out.sort_by_key(|e| e.start);
+    out
 }
@@ -9,15 +9,24 @@ use std::path::PathBuf;
 
 /// Minimal, crate-visible representation of CLI args used by the library.
 /// Le binaire est responsable de construire cette struct à partir du type clap local.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct CliArgs {
     pub focus: Option<u64>,
     pub short: Option<u64>,
     pub long: Option<u64>,
     pub cycles: Option<u8>,
+    pub long_every: Option<u8>,
+    pub rounds: Option<u8>,
+    pub endless: bool,
+    pub flowtime: bool,
+    pub break_ratio: Option<u64>,
     pub task: Option<String>,
     pub preset: Option<PathBuf>,
+    /// named preset from the preset store (`preset list`)
+    pub preset_name: Option<String>,
     pub gui: bool,
+    pub beep: bool,
+    pub notify: bool,
 }
 
 // Re-export convenient types commonly used by binaries/tests
@@ -1,7 +1,12 @@
-use crate::{CliArgs, Config, Journal};
+use crate::domain::config::Flowtime;
+use crate::domain::events::{next_event, SessionEvent};
+use crate::domain::layers::ResolvedConfig;
+use crate::domain::session::SessionControl;
+use crate::domain::validation::ValidationError;
+use crate::infra::status_file::StatusFile;
+use crate::infra::storage::{timestamp, SessionEntry};
+use crate::{CliArgs, Config, Journal, SessionRunner};
 use dioxus::prelude::*;
-use std::time::Duration;
-use tokio::time::sleep;
 
 #[derive(Clone, Debug, PartialEq)]
 enum AppState {
@@ -11,20 +16,54 @@ enum AppState {
 }
 
 pub fn run_gui(cli_args: CliArgs) {
-    // Initial configuration based on CLI arguments
-    let initial_config = Config::from_cli_and_preset(&cli_args).unwrap_or_default();
+    // Initial configuration based on CLI arguments; invalid values are kept
+    // in the form, with their errors next to the inputs
+    let (initial_config, initial_errors) = match Config::from_cli_and_preset(&cli_args) {
+        Ok(cfg) => (cfg, None),
+        Err(e) => match e.downcast::<ValidationError>() {
+            Ok(invalid) => {
+                let cfg = crate::domain::layers::layers(&cli_args)
+                    .and_then(|l| ResolvedConfig::merge(&l))
+                    .map(|r| r.config)
+                    .unwrap_or_default();
+                (cfg, Some(invalid))
+            }
+            Err(e) => {
+                tracing::warn!("Using the default configuration: {:#}", e);
+                (Config::default(), None)
+            }
+        },
+    };
 
     // Launch Dioxus desktop app
     dioxus::desktop::launch::launch_virtual_dom(
-        dioxus::prelude::VirtualDom::new_with_props(App, AppProps { initial_config }),
+        dioxus::prelude::VirtualDom::new_with_props(
+            App,
+            AppProps {
+                initial_config,
+                initial_errors,
+                beep: cli_args.beep,
+                notify: cli_args.notify,
+            },
+        ),
         dioxus::desktop::Config::new()
             .with_window(dioxus::desktop::WindowBuilder::new().with_title("Pomodoro")),
     );
 }
 
+/// Sent to the session coroutine to start a run.
+#[derive(Clone, Debug)]
+enum SessionRequest {
+    New(Config),
+    Resume(SessionEntry),
+}
+
 #[derive(Props, Clone, PartialEq)]
 pub struct AppProps {
     initial_config: Config,
+    initial_errors: Option<ValidationError>,
+    beep: bool,
+    notify: bool,
 }
 
 use futures_util::stream::StreamExt;
@@ -36,50 +75,95 @@ fn App(props: AppProps) -> Element {
 
     let current_segment_label = use_signal(String::new);
     let current_remaining_seconds = use_signal(|| 0u64);
+    let counting_up = use_signal(|| false);
+    let mut paused = use_signal(|| false);
+    let calendar = props.initial_config.calendar();
+    let mut unfinished = use_signal(|| {
+        Journal::open_default(calendar)
+            .ok()
+            .and_then(|j| j.latest_unfinished().ok().flatten())
+    });
+
+    // validation problems of the form, shown next to their inputs
+    let mut config_errors = use_signal(|| props.initial_errors.clone());
+    let control = use_signal(|| None::<SessionControl>);
+    let outcome = use_signal(String::new);
+    let (beep, notify) = (props.beep, props.notify);
 
-    let coroutine = use_coroutine(|mut rx: UnboundedReceiver<Config>| {
+    // The session itself runs in `SessionRunner`; this coroutine only follows
+    // its event stream to update the signals.
+    let coroutine = use_coroutine(|mut rx: UnboundedReceiver<SessionRequest>| {
         let mut state = state;
         let mut current_segment_label = current_segment_label;
         let mut current_remaining_seconds = current_remaining_seconds;
+        let mut counting_up = counting_up;
+        let mut paused = paused;
+        let mut control = control;
+        let mut outcome = outcome;
 
         async move {
-            while let Some(cfg) = rx.next().await {
+            while let Some(req) = rx.next().await {
+                paused.set(false);
                 state.set(AppState::Running);
 
-                let schedule = cfg.clone().into_schedule();
-
-                // Open Journal and create SessionEntry
-                let journal_res = Journal::open_default();
-                if let Ok(journal) = journal_res {
-                    if let Ok(mut entry) = crate::infra::storage::SessionEntry::new(&cfg) {
-                        for seg in schedule.segments {
-                            let kind_label = match seg.kind {
-                                crate::domain::schedule::SegmentKind::Focus => "FOCUS",
-                                crate::domain::schedule::SegmentKind::ShortBreak => "BREAK",
-                                crate::domain::schedule::SegmentKind::LongBreak => "LONG BREAK",
-                            };
-
-                            current_segment_label.set(kind_label.to_string());
-                            let mut remaining = seg.seconds;
-
-                            while remaining > 0 {
-                                current_remaining_seconds.set(remaining);
-                                sleep(Duration::from_secs(1)).await;
-                                remaining = remaining.saturating_sub(1);
-                            }
-
-                            // update journal partial after each segment
-                            entry
-                                .segments
-                                .push(format!("{}:{}s", kind_label, seg.seconds));
-                            entry.last_updated = time::OffsetDateTime::now_utc();
-                            let _ = entry.append_to_path(&journal.path);
+                let cfg = match &req {
+                    SessionRequest::New(cfg) => cfg.clone(),
+                    SessionRequest::Resume(e) => e.cfg.clone(),
+                };
+                match Journal::open_default(cfg.calendar()) {
+                    Ok(journal) => {
+                        let mut runner = SessionRunner::new(cfg, journal, beep, notify);
+                        if let Ok(f) = StatusFile::open_default() {
+                            runner = runner.with_status_file(f);
+                        }
+                        if let SessionRequest::Resume(e) = req {
+                            runner.resume_from(e);
                         }
+                        control.set(Some(runner.control()));
 
-                        entry.end = Some(time::OffsetDateTime::now_utc());
-                        entry.state = crate::infra::storage::SessionState::Completed;
-                        let _ = journal.append(&entry);
+                        let mut events = runner.subscribe();
+                        let ui = async {
+                            while let Some(ev) = next_event(&mut events).await {
+                                match &ev {
+                                    SessionEvent::SegmentStarted { segment, .. } => {
+                                        current_segment_label.set(segment.label().to_string());
+                                        current_remaining_seconds.set(segment.seconds);
+                                        counting_up.set(segment.kind.is_open_ended());
+                                    }
+                                    SessionEvent::Tick {
+                                        remaining_secs,
+                                        elapsed_secs,
+                                        ..
+                                    } => {
+                                        // flow segments show elapsed time instead
+                                        if *counting_up.peek() {
+                                            current_remaining_seconds.set(*elapsed_secs);
+                                        } else {
+                                            current_remaining_seconds.set(*remaining_secs);
+                                        }
+                                    }
+                                    SessionEvent::Paused { .. } => paused.set(true),
+                                    SessionEvent::Resumed { .. } => paused.set(false),
+                                    SessionEvent::Interrupted { .. } => {
+                                        outcome.set("Session Interrupted".to_string());
+                                    }
+                                    SessionEvent::Finished { .. } => {
+                                        outcome.set("Session Finished!".to_string());
+                                    }
+                                    SessionEvent::Failed { message } => {
+                                        outcome.set(format!("Session failed: {}", message));
+                                    }
+                                    _ => {}
+                                }
+                                if ev.is_last() {
+                                    break;
+                                }
+                            }
+                        };
+                        let _ = futures_util::join!(runner.run(), ui);
+                        control.set(None);
                     }
+                    Err(e) => outcome.set(format!("Could not open journal: {:#}", e)),
                 }
 
                 state.set(AppState::Finished);
@@ -90,11 +174,67 @@ fn App(props: AppProps) -> Element {
     let state_val = state.read().clone();
     match state_val {
         AppState::Configuring => {
+            let resume_text = unfinished
+                .read()
+                .as_ref()
+                .map(|e| {
+                    format!(
+                        "Unfinished session from {} ({}, {})",
+                        timestamp(calendar.local_time(e.start)),
+                        e.cfg.task.as_deref().unwrap_or("no task"),
+                        e.progress()
+                    )
+                })
+                .unwrap_or_default();
+
+            let errors = config_errors.read().clone();
+            let field_error = |field: &str| {
+                errors
+                    .as_ref()
+                    .and_then(|e| e.field(field))
+                    .map(|e| format!("must be {}", e.expected))
+                    .unwrap_or_default()
+            };
+            let (focus_err, short_err, long_err, cycles_err, task_err) = (
+                field_error("focus_min"),
+                field_error("short_min"),
+                field_error("long_min"),
+                field_error("cycles"),
+                field_error("task"),
+            );
+            // problems in settings the form does not show (preset, config files)
+            let other_errors: Vec<String> = errors
+                .iter()
+                .flat_map(|e| e.errors.iter())
+                .filter(|e| {
+                    !["focus_min", "short_min", "long_min", "cycles", "task"]
+                        .contains(&e.field.as_str())
+                })
+                .map(|e| e.to_string())
+                .collect();
+
             rsx! {
                 div {
                     style: "padding: 20px; font-family: sans-serif;",
                     h1 { "Pomodoro Configuration" }
 
+                    if !resume_text.is_empty() {
+                        div { margin_bottom: "20px",
+                            p { "{resume_text}" }
+                            button {
+                                style: "padding: 10px 20px; font-size: 16px;",
+                                onclick: move |_| {
+                                    let taken = unfinished.write().take();
+                                    if let Some(entry) = taken {
+                                        config.set(entry.cfg.clone());
+                                        coroutine.send(SessionRequest::Resume(entry));
+                                    }
+                                },
+                                "Resume Session"
+                            }
+                        }
+                    }
+
                     div { margin_bottom: "10px",
                         label { "Focus (min): " }
                         input {
@@ -106,6 +246,9 @@ fn App(props: AppProps) -> Element {
                                 }
                             }
                         }
+                        if !focus_err.is_empty() {
+                            span { style: "color: #c00; margin-left: 10px;", "{focus_err}" }
+                        }
                     }
                     div { margin_bottom: "10px",
                         label { "Short Break (min): " }
@@ -118,6 +261,9 @@ fn App(props: AppProps) -> Element {
                                 }
                             }
                         }
+                        if !short_err.is_empty() {
+                            span { style: "color: #c00; margin-left: 10px;", "{short_err}" }
+                        }
                     }
                     div { margin_bottom: "10px",
                         label { "Long Break (min): " }
@@ -130,6 +276,9 @@ fn App(props: AppProps) -> Element {
                                 }
                             }
                         }
+                        if !long_err.is_empty() {
+                            span { style: "color: #c00; margin-left: 10px;", "{long_err}" }
+                        }
                     }
                     div { margin_bottom: "10px",
                         label { "Cycles: " }
@@ -142,6 +291,19 @@ fn App(props: AppProps) -> Element {
                                 }
                             }
                         }
+                        if !cycles_err.is_empty() {
+                            span { style: "color: #c00; margin-left: 10px;", "{cycles_err}" }
+                        }
+                    }
+                    div { margin_bottom: "10px",
+                        label { "Flowtime (focus counts up, break = focus / 5): " }
+                        input {
+                            "type": "checkbox",
+                            checked: config.read().flowtime.is_some(),
+                            onchange: move |evt| {
+                                config.write().flowtime = evt.checked().then(Flowtime::default);
+                            }
+                        }
                     }
                     div { margin_bottom: "10px",
                         label { "Task: " }
@@ -152,12 +314,27 @@ fn App(props: AppProps) -> Element {
                                 config.write().task = if evt.value().is_empty() { None } else { Some(evt.value().clone()) };
                             }
                         }
+                        if !task_err.is_empty() {
+                            span { style: "color: #c00; margin-left: 10px;", "{task_err}" }
+                        }
+                    }
+
+                    for msg in other_errors {
+                        p { style: "color: #c00;", "{msg}" }
                     }
 
                     button {
                         style: "padding: 10px 20px; font-size: 16px;",
                         onclick: move |_| {
-                            coroutine.send(config.read().clone());
+                            let cfg = config.read().clone();
+                            match cfg.validate() {
+                                Ok(()) => {
+                                    config_errors.set(None);
+                                    unfinished.set(None);
+                                    coroutine.send(SessionRequest::New(cfg));
+                                }
+                                Err(e) => config_errors.set(Some(e)),
+                            }
                         },
                         "Start Session"
                     }
@@ -170,6 +347,8 @@ fn App(props: AppProps) -> Element {
             let mins = remaining / 60;
             let secs = remaining % 60;
             let task_name = config.read().task.clone().unwrap_or_default();
+            let is_paused = *paused.read();
+            let is_flow = *counting_up.read();
 
             rsx! {
                 div {
@@ -182,6 +361,39 @@ fn App(props: AppProps) -> Element {
                         style: "font-size: 80px; font-weight: bold; margin: 20px 0;",
                         "{mins:02}:{secs:02}"
                     }
+                    if is_flow {
+                        button {
+                            style: "padding: 10px 20px; font-size: 16px; margin-right: 10px;",
+                            onclick: move |_| {
+                                if let Some(c) = control.read().as_ref() {
+                                    c.skip();
+                                }
+                            },
+                            "Take a Break"
+                        }
+                    }
+                    button {
+                        style: "padding: 10px 20px; font-size: 16px;",
+                        onclick: move |_| {
+                            let now_paused = control.read().as_ref().map(|c| {
+                                c.toggle_pause();
+                                c.is_paused()
+                            });
+                            if let Some(p) = now_paused {
+                                paused.set(p);
+                            }
+                        },
+                        if is_paused { "Resume" } else { "Pause" }
+                    }
+                    button {
+                        style: "padding: 10px 20px; font-size: 16px; margin-left: 10px;",
+                        onclick: move |_| {
+                            if let Some(c) = control.read().as_ref() {
+                                c.interrupt();
+                            }
+                        },
+                        "Stop"
+                    }
                 }
             }
         }
@@ -189,7 +401,7 @@ fn App(props: AppProps) -> Element {
             rsx! {
                 div {
                     style: "padding: 50px; text-align: center; font-family: sans-serif;",
-                    h1 { "Session Finished!" }
+                    h1 { "{outcome}" }
                     button {
                         style: "padding: 10px 20px; font-size: 16px;",
                         onclick: move |_| {
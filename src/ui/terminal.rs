@@ -1,8 +1,10 @@
+use crate::domain::events::{next_event, EventReceiver, SessionEvent};
+use crate::domain::session::SessionControl;
 use anyhow::Context;
 use anyhow::Result;
 use indicatif::{ProgressBar, ProgressStyle};
-use std::time::Duration;
-use tokio::time::sleep;
+use std::io::{BufRead, IsTerminal};
+use tracing::debug;
 
 pub struct Terminal {
     task: Option<String>,
@@ -13,25 +15,102 @@ impl Terminal {
         Ok(Self { task })
     }
 
-    pub async fn show_segment(&mut self, label: String, seconds: u64) -> Result<()> {
-        let style =
-            ProgressStyle::with_template("{prefix} {bar:40.cyan/blue} {pos}/{len}s {elapsed}")
-                .context("invalid progress style template")?;
-        let pb = ProgressBar::new(seconds);
-        pb.set_style(style);
-        pb.set_prefix(format!(
-            "[{}] {}",
-            label,
-            self.task.clone().unwrap_or_default()
-        ));
+    /// Line commands on stdin (only when it is a tty): Enter toggles
+    /// pause/resume, `s` skips the current segment, `q` stops the session.
+    pub fn listen_for_pause(&self, control: SessionControl) {
+        let stdin = std::io::stdin();
+        if !stdin.is_terminal() {
+            return;
+        }
+        eprintln!("Enter: pause/resume, s+Enter: skip segment, q+Enter or Ctrl-C: stop.");
+        std::thread::spawn(move || {
+            for line in stdin.lock().lines() {
+                let Ok(line) = line else {
+                    break;
+                };
+                match line.trim() {
+                    "s" => control.skip(),
+                    "q" => control.interrupt(),
+                    _ => {
+                        control.toggle_pause();
+                    }
+                }
+            }
+            debug!("stdin closed, pause listener stopped");
+        });
+    }
+
+    /// Render the session from its event stream: one progress bar per segment.
+    pub async fn follow(mut self, mut rx: EventReceiver) -> Result<()> {
+        let style = ProgressStyle::with_template(
+            "{prefix} {bar:40.cyan/blue} {pos}/{len}s {elapsed} {msg}",
+        )
+        .context("invalid progress style template")?;
+        let flow_style = ProgressStyle::with_template(
+            "{prefix} {spinner} {pos}s elapsed (s + Enter: take a break) {msg}",
+        )
+        .context("invalid progress style template")?;
+        let mut pb: Option<ProgressBar> = None;
 
-        let mut remaining = seconds;
-        while remaining > 0 {
-            pb.set_position(seconds - remaining);
-            sleep(Duration::from_secs(1)).await;
-            remaining = remaining.saturating_sub(1);
+        while let Some(ev) = next_event(&mut rx).await {
+            match &ev {
+                SessionEvent::SessionStarted { entry, .. } => {
+                    self.task = entry.cfg.task.clone();
+                }
+                SessionEvent::SegmentStarted { segment, .. } => {
+                    // open-ended (flow) segments count up: spinner + elapsed time
+                    let bar = if segment.kind.is_open_ended() {
+                        let bar = ProgressBar::new_spinner();
+                        bar.set_style(flow_style.clone());
+                        bar
+                    } else {
+                        let bar = ProgressBar::new(segment.seconds);
+                        bar.set_style(style.clone());
+                        bar
+                    };
+                    bar.set_prefix(format!(
+                        "[{}] {}",
+                        segment.label(),
+                        self.task.clone().unwrap_or_default()
+                    ));
+                    pb = Some(bar);
+                }
+                SessionEvent::Tick { elapsed_secs, .. } => {
+                    if let Some(bar) = &pb {
+                        bar.set_position(*elapsed_secs);
+                    }
+                }
+                SessionEvent::Paused { .. } => {
+                    if let Some(bar) = &pb {
+                        bar.set_message("PAUSED");
+                    }
+                }
+                SessionEvent::Resumed { .. } => {
+                    if let Some(bar) = &pb {
+                        bar.set_message("");
+                    }
+                }
+                SessionEvent::SegmentCompleted { .. } => {
+                    if let Some(bar) = pb.take() {
+                        bar.finish_with_message("done");
+                    }
+                }
+                SessionEvent::Interrupted { .. } => {
+                    if let Some(bar) = pb.take() {
+                        bar.abandon_with_message("interrupted");
+                    }
+                }
+                SessionEvent::Failed { message } => {
+                    if let Some(bar) = pb.take() {
+                        bar.abandon_with_message(message.clone());
+                    }
+                }
+                _ => {}
+            }
+            if ev.is_last() {
+                break;
+            }
         }
-        pb.finish_with_message("done");
         Ok(())
     }
 }
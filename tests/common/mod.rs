@@ -0,0 +1,54 @@
+//! Helpers shared by the integration tests (`mod common;` in each file).
+#![allow(dead_code)]
+
+use pomodoro_cli::domain::config::Config;
+use pomodoro_cli::infra::storage::SessionEntry;
+use pomodoro_cli::Journal;
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use time::OffsetDateTime;
+
+/// An empty `pomodoro-<name>-<pid>` directory in the system temp dir,
+/// removed with everything in it when dropped.
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+    pub fn new(name: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!("pomodoro-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        TempDir(dir)
+    }
+
+    /// JSONL journal of this dir, writing to `file`.
+    pub fn journal(&self, file: impl AsRef<Path>) -> Journal {
+        Journal::jsonl(self.0.clone(), self.0.join(file))
+    }
+}
+
+impl Deref for TempDir {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+/// A new session of the default config for `task`, started (and last
+/// updated) at `start`.
+pub fn entry(start: OffsetDateTime, task: &str) -> SessionEntry {
+    let cfg = Config {
+        task: Some(task.to_string()),
+        ..Config::default()
+    };
+    let mut e = SessionEntry::new(&cfg).unwrap();
+    e.start = start;
+    e.last_updated = start;
+    e
+}
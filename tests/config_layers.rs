@@ -0,0 +1,78 @@
+mod common;
+
+use common::TempDir;
+use pomodoro_cli::domain::layers::{load_with, ConfigSource, PROJECT_FILE};
+use pomodoro_cli::infra::presets::PresetStore;
+use pomodoro_cli::CliArgs;
+use std::fs;
+
+#[test]
+fn layers_apply_in_documented_order() {
+    let root = TempDir::new("layers");
+    let sub = root.join("project/src/deep");
+    fs::create_dir_all(&sub).unwrap();
+    let user = root.join("config.toml");
+    fs::write(
+        &user,
+        "focus_min = 30\nshort_min = 10\nlong_min = 20\ntask = \"from user\"\n",
+    )
+    .unwrap();
+    let project = root.join("project").join(PROJECT_FILE);
+    fs::write(
+        &project,
+        "short_min = 7\ncycles = 6\n[flowtime]\nratio = 4\n",
+    )
+    .unwrap();
+    let preset = root.join("preset.json");
+    fs::write(&preset, r#"{"long_min": 25, "task": "from preset"}"#).unwrap();
+
+    let env = vec![
+        ("POMODORO_CYCLES".to_string(), "3".to_string()),
+        ("POMODORO_FLOWTIME".to_string(), "false".to_string()),
+        ("HOME".to_string(), "/nowhere".to_string()),
+    ];
+    let cli = CliArgs {
+        focus: Some(45),
+        preset: Some(preset.clone()),
+        ..CliArgs::default()
+    };
+    let presets = PresetStore {
+        dir: root.join("presets"),
+    };
+    let resolved = load_with(&cli, Some(&user), &presets, &sub, env).unwrap();
+    let cfg = &resolved.config;
+
+    assert_eq!(cfg.focus_min, 45);
+    assert_eq!(resolved.source_of("focus_min"), ConfigSource::Cli);
+    assert_eq!(cfg.short_min, 7);
+    assert_eq!(
+        resolved.source_of("short_min"),
+        ConfigSource::ProjectFile(project.clone())
+    );
+    assert_eq!(cfg.long_min, 25);
+    assert_eq!(resolved.source_of("long_min"), ConfigSource::Preset(preset));
+    assert_eq!(cfg.cycles, 3);
+    assert_eq!(
+        resolved.source_of("cycles"),
+        ConfigSource::Env("POMODORO_CYCLES".into())
+    );
+    assert_eq!(cfg.flowtime, None);
+    assert_eq!(cfg.task.as_deref(), Some("from preset"));
+    assert_eq!(resolved.source_of("rounds"), ConfigSource::Default);
+
+    let described = resolved.describe();
+    let (_, value, source) = described.iter().find(|(k, _, _)| k == "short_min").unwrap();
+    assert_eq!(value, 7);
+    assert_eq!(source, &ConfigSource::ProjectFile(project));
+
+    // bad values point at their source
+    let err = load_with(
+        &CliArgs::default(),
+        Some(&user),
+        &presets,
+        &sub,
+        vec![("POMODORO_ROUNDS".to_string(), "many".to_string())],
+    )
+    .unwrap_err();
+    assert!(format!("{:#}", err).contains("POMODORO_ROUNDS"));
+}
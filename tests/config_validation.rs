@@ -1,4 +1,11 @@
-use pomodoro_cli::domain::config::Config;
+mod common;
+
+use common::TempDir;
+use pomodoro_cli::domain::config::{Config, Flowtime};
+use pomodoro_cli::domain::layers::{layers_with, load_with, ConfigSource, ResolvedConfig};
+use pomodoro_cli::domain::schedule::{SegmentKind, SegmentSpec};
+use pomodoro_cli::domain::validation::ValidationError;
+use pomodoro_cli::infra::presets::PresetStore;
 
 #[test]
 fn config_accepts_defaults() {
@@ -12,3 +19,123 @@ fn config_rejects_too_short_focus() {
     c.focus_min = 1;
     assert!(c.validate().is_err());
 }
+
+#[test]
+fn config_validates_segment_list() {
+    let ok: Config = serde_json::from_str(
+        r#"{"task": "x", "segments": [{"kind": "focus", "minutes": 90}, {"kind": "long_break", "minutes": 30}]}"#,
+    )
+    .unwrap();
+    assert!(ok.validate().is_ok());
+    assert_eq!(ok.total_cycles(), Some(1));
+
+    let no_focus = Config {
+        segments: vec![SegmentSpec {
+            kind: SegmentKind::ShortBreak,
+            minutes: 5,
+            label: None,
+        }],
+        ..Config::default()
+    };
+    assert!(no_focus.validate().is_err());
+
+    let too_long = Config {
+        segments: vec![SegmentSpec {
+            kind: SegmentKind::Focus,
+            minutes: 300,
+            label: Some("Marathon".into()),
+        }],
+        ..Config::default()
+    };
+    assert!(too_long.validate().is_err());
+}
+
+#[test]
+fn flowtime_break_is_proportional_and_clamped() {
+    let flow = Flowtime::default();
+    assert_eq!(flow.break_secs(50 * 60), 10 * 60);
+    assert_eq!(flow.break_secs(60), 60);
+    assert_eq!(flow.break_secs(300 * 60), 30 * 60);
+
+    let with_rounds = Config {
+        flowtime: Some(flow),
+        rounds: 2,
+        ..Config::default()
+    };
+    assert!(with_rounds.validate().is_err());
+}
+
+#[test]
+fn validation_reports_every_field() {
+    let c = Config {
+        focus_min: 1,
+        short_min: 45,
+        cycles: 0,
+        task: Some("x".repeat(81)),
+        ..Config::default()
+    };
+    let err = c.validate().unwrap_err();
+    let fields: Vec<&str> = err.errors.iter().map(|e| e.field.as_str()).collect();
+    assert_eq!(fields, ["task", "focus_min", "short_min", "cycles"]);
+
+    let focus = err.field("focus_min").unwrap();
+    assert_eq!(focus.value, "1");
+    assert_eq!(focus.expected, "between 5 and 120 minutes");
+    let text = err.to_string();
+    assert!(text.starts_with("invalid configuration (4 problems):"));
+    assert!(text.contains("short_min = 45: must be between 1 and 30 minutes"));
+}
+
+#[test]
+fn validation_errors_name_their_source() {
+    let dir = TempDir::new("verr");
+    let preset = dir.join("bad.json");
+    std::fs::write(
+        &preset,
+        r#"{"segments": [{"kind": "focus", "minutes": 0}, {"kind": "break", "minutes": 5, "label": ""}]}"#,
+    )
+    .unwrap();
+    let cli = pomodoro_cli::CliArgs {
+        preset: Some(preset.clone()),
+        ..Default::default()
+    };
+    let err = load_with(
+        &cli,
+        None,
+        &PresetStore {
+            dir: dir.join("presets"),
+        },
+        &dir,
+        Vec::new(),
+    )
+    .unwrap_err();
+    let err = err.downcast_ref::<ValidationError>().unwrap();
+    assert_eq!(err.errors.len(), 2);
+    assert_eq!(err.errors[0].field, "segments[0].minutes");
+    assert_eq!(err.errors[1].field, "segments[1].label");
+    assert_eq!(err.errors[0].source, Some(ConfigSource::Preset(preset)));
+}
+
+#[test]
+fn invalid_values_can_be_shown_for_editing() {
+    let dir = TempDir::new("vedit");
+    let cli = pomodoro_cli::CliArgs {
+        focus: Some(1),
+        task: Some("Review".into()),
+        ..Default::default()
+    };
+    let presets = PresetStore {
+        dir: dir.join("presets"),
+    };
+    let err = load_with(&cli, None, &presets, &dir, Vec::new()).unwrap_err();
+    assert!(err
+        .downcast_ref::<ValidationError>()
+        .unwrap()
+        .field("focus_min")
+        .is_some());
+    // the GUI seeds its form with the values as given
+    let layers = layers_with(&cli, None, &presets, &dir, Vec::new()).unwrap();
+    let merged = ResolvedConfig::merge(&layers).unwrap().config;
+    assert_eq!(merged.focus_min, 1);
+    assert_eq!(merged.task.as_deref(), Some("Review"));
+}
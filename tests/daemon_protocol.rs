@@ -0,0 +1,135 @@
+#![cfg(unix)]
+
+mod common;
+
+use common::TempDir;
+use pomodoro_cli::domain::layers::load_with;
+use pomodoro_cli::domain::status::RunState;
+use pomodoro_cli::infra::daemon::{send, Daemon, Request, Response};
+use pomodoro_cli::infra::presets::PresetStore;
+use std::path::Path;
+use std::time::Duration;
+
+async fn ask(socket: &Path, req: Request) -> Response {
+    let socket = socket.to_path_buf();
+    tokio::task::spawn_blocking(move || send(&socket, &req).unwrap())
+        .await
+        .unwrap()
+}
+
+async fn wait_for_state(socket: &Path, state: RunState) -> Response {
+    for _ in 0..100 {
+        let resp = ask(socket, Request::Status).await;
+        if resp.status.state == state {
+            return resp;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    panic!("daemon never reached {:?}", state);
+}
+
+fn start(preset_name: Option<&str>) -> Request {
+    Request::Start {
+        focus: None,
+        short: None,
+        long: None,
+        cycles: None,
+        long_every: None,
+        rounds: None,
+        endless: false,
+        flowtime: false,
+        break_ratio: None,
+        task: Some("daemon test".into()),
+        preset: None,
+        preset_name: preset_name.map(str::to_string),
+    }
+}
+
+#[test]
+fn request_wire_format() {
+    let req: Request = serde_json::from_str(r#"{"cmd":"start","focus":50,"task":"x"}"#).unwrap();
+    assert_eq!(
+        req,
+        Request::Start {
+            focus: Some(50),
+            short: None,
+            long: None,
+            cycles: None,
+            long_every: None,
+            rounds: None,
+            endless: false,
+            flowtime: false,
+            break_ratio: None,
+            task: Some("x".into()),
+            preset: None,
+            preset_name: None,
+        }
+    );
+    assert_eq!(
+        serde_json::to_string(&Request::Pause).unwrap(),
+        r#"{"cmd":"pause"}"#
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn daemon_serves_control_commands() {
+    let dir = TempDir::new("daemon");
+    let socket = dir.join("test.sock");
+    let journal = dir.journal("journal-test.jsonl");
+
+    let (tx, rx) = tokio::sync::oneshot::channel::<()>();
+    // no user config, project file or env: only the request counts
+    let presets = PresetStore {
+        dir: dir.to_path_buf(),
+    };
+    let cwd = dir.to_path_buf();
+    let daemon = Daemon::new(socket.clone(), false, false)
+        .with_journal(journal.clone())
+        .with_status_file(Some(dir.join("status.json")))
+        .with_config_loader(move |cli| {
+            Ok(load_with(cli, None, &presets, &cwd, std::iter::empty())?.config)
+        });
+    let server = tokio::spawn(daemon.serve(async move {
+        let _ = rx.await;
+    }));
+    while !socket.exists() {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+
+    // one daemon per socket
+    let second = Daemon::new(socket.clone(), false, false)
+        .serve(std::future::pending())
+        .await;
+    assert!(second.is_err());
+
+    let resp = ask(&socket, Request::Status).await;
+    assert_eq!(resp.status.state, RunState::Idle);
+    let resp = ask(&socket, Request::Pause).await;
+    assert!(!resp.ok);
+
+    // a start that fails leaves the daemon idle
+    let resp = ask(&socket, start(Some("no-such-preset"))).await;
+    assert!(!resp.ok);
+    assert_eq!(resp.status.state, RunState::Idle);
+
+    // two starts at once: only one session
+    let (a, b) = tokio::join!(ask(&socket, start(None)), ask(&socket, start(None)));
+    assert!(a.ok != b.ok, "{:?} / {:?}", a.message, b.message);
+    wait_for_state(&socket, RunState::Running).await;
+
+    assert!(ask(&socket, Request::Pause).await.ok);
+    let resp = wait_for_state(&socket, RunState::Paused).await;
+    assert_eq!(resp.status.task.as_deref(), Some("daemon test"));
+    assert!(ask(&socket, Request::Resume).await.ok);
+    assert!(ask(&socket, Request::Stop).await.ok);
+    wait_for_state(&socket, RunState::Interrupted).await;
+
+    tx.send(()).unwrap();
+    server.await.unwrap().unwrap();
+    assert!(!socket.exists());
+    assert!(dir.join("status.json").exists());
+
+    let last = journal.latest_unfinished().unwrap().unwrap();
+    assert_eq!(last.cfg.task.as_deref(), Some("daemon test"));
+    assert_eq!(last.pauses.len(), 1);
+}
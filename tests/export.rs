@@ -0,0 +1,164 @@
+mod common;
+
+use common::{entry, TempDir};
+use pomodoro_cli::domain::clock::Calendar;
+use pomodoro_cli::domain::config::Config;
+use pomodoro_cli::infra::export::{csv, ics, segments_csv, IcsEvents};
+use pomodoro_cli::infra::export::{period, ExportFormat, Output};
+use pomodoro_cli::infra::storage::{JournalQuery, SessionState};
+use time::macros::{date, datetime};
+
+fn range(from: time::Date, to: time::Date) -> JournalQuery {
+    JournalQuery {
+        from: Some(from),
+        to: Some(to),
+        ..JournalQuery::default()
+    }
+}
+
+#[test]
+fn exports_a_day_range_to_the_chosen_file() {
+    let dir = TempDir::new("export");
+    let journal = dir.journal("journal-2024-05-03.jsonl");
+    for (start, task) in [
+        (datetime!(2024-04-30 09:00 UTC), "april"),
+        (datetime!(2024-05-01 09:00 UTC), "first"),
+        (datetime!(2024-05-03 09:00 UTC), "third"),
+    ] {
+        journal.append(&entry(start, task)).unwrap();
+    }
+
+    let out = dir.join("review.md");
+    let n = journal
+        .export(
+            ExportFormat::Markdown,
+            &range(date!(2024 - 05 - 01), date!(2024 - 05 - 07)),
+            &Output::File(out.clone()),
+        )
+        .unwrap();
+    assert_eq!(n, 2);
+    let md = std::fs::read_to_string(&out).unwrap();
+    assert!(md.starts_with("# Pomodoro journal (2024-05-01 → 2024-05-07)"));
+    assert!(md.contains("\"first\"") && md.contains("\"third\""));
+    assert!(!md.contains("april"));
+
+    let out = dir.join("april.csv");
+    journal
+        .export(
+            ExportFormat::Csv,
+            &range(date!(2024 - 04 - 01), date!(2024 - 04 - 30)),
+            &Output::File(out.clone()),
+        )
+        .unwrap();
+    let csv = std::fs::read_to_string(&out).unwrap();
+    assert_eq!(csv.lines().count(), 2);
+    assert!(csv.contains("april"));
+    // the today exports are separate files
+    assert!(!dir.join("journal-today.csv").exists());
+}
+
+#[test]
+fn formats_outputs_and_periods() {
+    assert_eq!(
+        "md".parse::<ExportFormat>().unwrap(),
+        ExportFormat::Markdown
+    );
+    assert_eq!("CSV".parse::<ExportFormat>().unwrap(), ExportFormat::Csv);
+    assert!("pdf".parse::<ExportFormat>().is_err());
+    assert_eq!("-".parse::<Output>().unwrap(), Output::Stdout);
+    assert_eq!(
+        "out.md".parse::<Output>().unwrap(),
+        Output::File("out.md".into())
+    );
+
+    let today = date!(2024 - 05 - 03);
+    assert_eq!(period(&range(today, today), today), "today");
+    assert_eq!(
+        period(&range(date!(2024 - 05 - 02), date!(2024 - 05 - 02)), today),
+        "2024-05-02"
+    );
+    assert_eq!(period(&JournalQuery::default(), today), "all days");
+}
+
+#[test]
+fn csv_quotes_fields_and_counts_focus() {
+    let cfg = Config {
+        cycles: 2,
+        ..Config::default()
+    };
+    let schedule = cfg.clone().into_schedule();
+    let mut e = entry(
+        datetime!(2024-05-02 09:00 UTC),
+        "Review \"PR, 12\"\nthen merge",
+    );
+    e.cfg.cycles = 2;
+    let focus = &schedule.segments[0];
+    e.start_segment(focus, e.start);
+    e.finish_segment(e.start + time::Duration::minutes(25));
+    e.begin_pause(e.start + time::Duration::minutes(26));
+    e.end_pause(e.start + time::Duration::minutes(30));
+    e.state = SessionState::Interrupted;
+
+    let out = csv(std::slice::from_ref(&e), &Calendar::default());
+    let mut lines = out.split("\r\n");
+    assert_eq!(
+        lines.next().unwrap(),
+        "id,start,end,state,task,planned_focus_min,focus_min,completed_focus,interruptions"
+    );
+    assert_eq!(
+        lines.next().unwrap(),
+        format!(
+            "{},2024-05-02T09:00:00Z,,Interrupted,\"Review \"\"PR, 12\"\"\nthen merge\",50.0,25.0,1,2",
+            e.id
+        )
+    );
+    assert_eq!(lines.next(), Some(""));
+
+    let out = segments_csv(&[e], &Calendar::default());
+    let rows: Vec<_> = out.split_terminator("\r\n").collect();
+    assert_eq!(rows.len(), 2);
+    assert!(rows[1]
+        .ends_with(",1,FOCUS,FOCUS,2024-05-02T09:00:00Z,2024-05-02T09:25:00Z,25.0,25.0,false"));
+}
+
+#[test]
+fn ics_has_one_stable_event_per_focus_segment() {
+    let cfg = Config {
+        cycles: 2,
+        ..Config::default()
+    };
+    let schedule = cfg.clone().into_schedule();
+    let mut e = entry(datetime!(2024-05-02 09:00 UTC), "Write; docs, part 1 ");
+    e.cfg.cycles = 2;
+    e.cfg.task = Some(format!("Write; docs, part 1 {}", "é".repeat(40)));
+    let mut at = e.start;
+    // focus, break, then the second focus still running
+    for seg in &schedule.segments[..3] {
+        e.start_segment(seg, at);
+        at += time::Duration::seconds(seg.seconds as i64);
+        if seg.cycle_index == 1 {
+            e.finish_segment(at);
+        }
+    }
+
+    let out = ics(std::slice::from_ref(&e), IcsEvents::FocusSegments);
+    assert!(out.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+    assert!(out.ends_with("END:VCALENDAR\r\n"));
+    assert!(out.split("\r\n").all(|l| l.len() <= 75));
+    assert_eq!(out.matches("BEGIN:VEVENT").count(), 1);
+    assert!(out.contains(&format!("UID:{}-0@pomodoro-cli\r\n", e.id)));
+    assert!(out.contains("DTSTART:20240502T090000Z\r\nDTEND:20240502T092500Z\r\n"));
+    let unfolded = out.replace("\r\n ", "");
+    assert!(unfolded.contains("SUMMARY:Write\\; docs\\, part 1 é"));
+    assert!(
+        unfolded.contains("DESCRIPTION:FOCUS cycle 1/2\\, completed (25 min)\\nSession Ongoing")
+    );
+
+    // same session later: same UID, newer stamp
+    e.state = SessionState::Completed;
+    e.last_updated = at;
+    let again = ics(std::slice::from_ref(&e), IcsEvents::Sessions);
+    assert_eq!(again.matches("BEGIN:VEVENT").count(), 1);
+    assert!(again.contains(&format!("UID:{}@pomodoro-cli\r\n", e.id)));
+    assert!(again.contains("DTSTAMP:20240502T095500Z"));
+}
@@ -0,0 +1,121 @@
+mod common;
+
+use common::TempDir;
+use pomodoro_cli::domain::config::Config;
+use pomodoro_cli::infra::storage::{legacy_id, JsonlJournal, SessionEntry, SessionState};
+use time::macros::{date, datetime};
+
+#[test]
+fn sessions_keep_the_last_snapshot_per_id() {
+    let dir = TempDir::new("ids");
+    let path = dir.join("journal-2024-05-02.jsonl");
+    let jsonl = JsonlJournal {
+        dir: dir.to_path_buf(),
+        path: path.clone(),
+    };
+    let journal = dir.journal(path);
+    let cfg = Config::default();
+    let schedule = cfg.clone().into_schedule();
+    let mut a = SessionEntry::new(&cfg).unwrap();
+    // same start, different session
+    let mut b = SessionEntry::new(&cfg).unwrap();
+    b.start = a.start;
+    assert_ne!(a.id, b.id);
+
+    for seg in &schedule.segments {
+        a.start_segment(seg, a.start);
+        a.finish_segment(a.start + time::Duration::seconds(seg.seconds as i64));
+        journal.append(&a).unwrap();
+    }
+    a.state = SessionState::Completed;
+    journal.append(&a).unwrap();
+    journal.append(&b).unwrap();
+
+    assert_eq!(jsonl.read_entries().unwrap().len(), 10);
+    let sessions = journal.sessions().unwrap();
+    assert_eq!(sessions.len(), 2);
+    let a_last = sessions.iter().find(|e| e.id == a.id).unwrap();
+    assert_eq!(a_last.state, SessionState::Completed);
+    assert_eq!(a_last.segments.len(), 8);
+
+    journal.export_csv_today().unwrap();
+    let csv = std::fs::read_to_string(dir.join("journal-today.csv")).unwrap();
+    assert_eq!(csv.lines().count(), 3);
+    assert!(csv.contains(&a.id.to_string()));
+}
+
+#[test]
+fn compact_rewrites_past_days_only() {
+    let dir = TempDir::new("compact");
+    let old = dir.join("journal-2024-05-01.jsonl");
+    // two snapshots of a session journaled before IDs existed
+    let legacy = r#"{"start":"2024-05-01T09:00:00Z","end":null,"cfg":{"focus_min":25,"short_min":5,"long_min":15,"cycles":1,"task":null},"state":"Ongoing","segments":[],"last_updated":"2024-05-01T09:00:00Z"}"#;
+    let done = legacy
+        .replace("Ongoing", "Completed")
+        .replace(r#""end":null"#, r#""end":"2024-05-01T09:45:00Z""#);
+    std::fs::write(&old, format!("{}\n{}\n", legacy, done)).unwrap();
+
+    let today = JsonlJournal {
+        dir: dir.to_path_buf(),
+        path: dir.join("journal-2024-05-02.jsonl"),
+    };
+    let e = SessionEntry::new(&Config::default()).unwrap();
+    e.append_to_path(&today.path).unwrap();
+    e.append_to_path(&today.path).unwrap();
+
+    let report = today.compact(date!(2024 - 05 - 02)).unwrap();
+    assert_eq!(
+        (report.files, report.lines_before, report.lines_after),
+        (1, 2, 1)
+    );
+    let lines = std::fs::read_to_string(&old).unwrap();
+    assert_eq!(lines.lines().count(), 1);
+    let kept: SessionEntry = serde_json::from_str(lines.trim()).unwrap();
+    assert_eq!(kept.state, SessionState::Completed);
+    assert_eq!(kept.id, legacy_id(datetime!(2024-05-01 09:00 UTC)));
+    assert_eq!(today.read_entries().unwrap().len(), 2);
+
+    // nothing left to do
+    assert_eq!(today.compact(date!(2024 - 05 - 02)).unwrap().files, 0);
+    assert_eq!(today.read_all().unwrap().len(), 2);
+}
+
+#[test]
+fn compact_keeps_lines_it_cannot_parse() {
+    let dir = TempDir::new("compact-unparsed");
+    let e = SessionEntry::new(&Config::default()).unwrap();
+    let line = serde_json::to_string(&e).unwrap();
+    let torn = &line[..line.len() / 2];
+    let newer = r#"{"schema":2,"session":"from a newer version"}"#;
+    // a valid line and a bad one: nothing to compact
+    let clean = dir.join("journal-2024-05-01.jsonl");
+    std::fs::write(&clean, format!("{}\n{}\n", line, newer)).unwrap();
+    // a duplicate snapshot around a torn line
+    let dup = dir.join("journal-2024-05-02.jsonl");
+    std::fs::write(&dup, format!("{}\n{}\n{}\n", line, torn, line)).unwrap();
+
+    let journal = JsonlJournal {
+        dir: dir.to_path_buf(),
+        path: dir.join("journal-2024-05-03.jsonl"),
+    };
+    let report = journal.compact(date!(2024 - 05 - 03)).unwrap();
+    assert_eq!(
+        (
+            report.files,
+            report.lines_before,
+            report.lines_after,
+            report.unparsed
+        ),
+        (1, 3, 2, 1)
+    );
+    assert_eq!(
+        std::fs::read_to_string(&clean).unwrap(),
+        format!("{}\n{}\n", line, newer)
+    );
+    assert_eq!(
+        std::fs::read_to_string(&dup).unwrap(),
+        format!("{}\n{}\n", line, torn)
+    );
+    // stable from then on
+    assert_eq!(journal.compact(date!(2024 - 05 - 03)).unwrap().files, 0);
+}
@@ -0,0 +1,24 @@
+use pomodoro_cli::domain::schedule::SegmentKind;
+use pomodoro_cli::infra::storage::SessionEntry;
+
+#[test]
+fn legacy_string_segments_still_load() {
+    let line = r#"{"start":"2024-05-02T09:00:00Z","end":"2024-05-02T09:35:00Z","cfg":{"focus_min":25,"short_min":5,"long_min":15,"cycles":2,"task":null},"state":"Completed","segments":["FOCUS:1500s","BREAK:300s","FOCUS:1500s","LONG BREAK:900s"],"last_updated":"2024-05-02T09:35:00Z"}"#;
+    let e: SessionEntry = serde_json::from_str(line).unwrap();
+    assert_eq!(e.segments.len(), 4);
+    assert_eq!(e.segments[1].kind, SegmentKind::ShortBreak);
+    assert_eq!(e.segments[1].cycle_index, 1);
+    assert_eq!(e.segments[3].kind, SegmentKind::LongBreak);
+    assert_eq!(e.segments[3].cycle_index, 2);
+    assert_eq!(e.focus_secs(), 3000);
+    assert!(e.pauses.is_empty());
+}
+
+#[test]
+fn typed_segments_roundtrip() {
+    let line = r#"{"start":"2024-05-02T09:00:00Z","end":null,"cfg":{"focus_min":25,"short_min":5,"long_min":15,"cycles":4,"task":"x"},"state":"Interrupted","segments":[{"kind":"Focus","cycle_index":1,"planned_secs":1500,"actual_secs":600,"start":"2024-05-02T09:00:00Z","end":"2024-05-02T09:10:00Z"}],"last_updated":"2024-05-02T09:10:00Z"}"#;
+    let e: SessionEntry = serde_json::from_str(line).unwrap();
+    assert!(!e.segments[0].is_complete());
+    let back: SessionEntry = serde_json::from_str(&serde_json::to_string(&e).unwrap()).unwrap();
+    assert_eq!(back.segments, e.segments);
+}
@@ -0,0 +1,121 @@
+mod common;
+
+use common::TempDir;
+use pomodoro_cli::domain::config::Config;
+use pomodoro_cli::infra::storage::{JournalBackend, JsonlJournal, SessionEntry};
+use std::io::Write;
+
+/// A JSONL journal in a fresh temp dir, removed with the returned guard.
+fn temp_journal(name: &str) -> (TempDir, JsonlJournal) {
+    let dir = TempDir::new(name);
+    let journal = JsonlJournal {
+        dir: dir.to_path_buf(),
+        path: dir.join("journal-test.jsonl"),
+    };
+    (dir, journal)
+}
+
+#[test]
+fn concurrent_appends_keep_every_line_whole() {
+    let (_dir, journal) = temp_journal("concurrent");
+    let writers: Vec<_> = (0..4)
+        .map(|w| {
+            let journal = journal.clone();
+            std::thread::spawn(move || {
+                let cfg = Config {
+                    // long enough to make interleaved writes likely without a lock
+                    task: Some(format!("writer {} {}", w, "x".repeat(60))),
+                    ..Config::default()
+                };
+                let entry = SessionEntry::new(&cfg).unwrap();
+                for _ in 0..50 {
+                    journal.append(&entry).unwrap();
+                }
+            })
+        })
+        .collect();
+    for w in writers {
+        w.join().unwrap();
+    }
+
+    let content = std::fs::read_to_string(&journal.path).unwrap();
+    assert_eq!(content.lines().count(), 200);
+    for line in content.lines() {
+        serde_json::from_str::<SessionEntry>(line).expect("whole line");
+    }
+    assert_eq!(journal.query_range(None, None).unwrap().len(), 4);
+}
+
+#[test]
+fn append_after_a_torn_line_keeps_earlier_entries() {
+    let (_dir, journal) = temp_journal("torn");
+    let first = SessionEntry::new(&Config::default()).unwrap();
+    journal.append(&first).unwrap();
+    // crash in the middle of the next write
+    let mut f = std::fs::OpenOptions::new()
+        .append(true)
+        .open(&journal.path)
+        .unwrap();
+    write!(f, "{{\"id\":\"0000").unwrap();
+    drop(f);
+
+    let second = SessionEntry::new(&Config::default()).unwrap();
+    journal.append(&second).unwrap();
+
+    let ids: Vec<_> = journal
+        .read_entries()
+        .unwrap()
+        .into_iter()
+        .map(|e| e.id)
+        .collect();
+    assert_eq!(ids, [first.id, second.id]);
+    assert_eq!(
+        std::fs::read_to_string(&journal.path)
+            .unwrap()
+            .lines()
+            .count(),
+        3
+    );
+}
+
+#[test]
+fn a_line_torn_inside_a_character_is_skipped() {
+    let (_dir, journal) = temp_journal("torn-utf8");
+    let cfg = Config {
+        task: Some("Révision".into()),
+        ..Config::default()
+    };
+    let first = SessionEntry::new(&cfg).unwrap();
+    journal.append(&first).unwrap();
+    // crash between the two bytes of "é"
+    let line = serde_json::to_vec(&SessionEntry::new(&cfg).unwrap()).unwrap();
+    let cut = line.windows(2).position(|w| w == "é".as_bytes()).unwrap() + 1;
+    let mut f = std::fs::OpenOptions::new()
+        .append(true)
+        .open(&journal.path)
+        .unwrap();
+    f.write_all(&line[..cut]).unwrap();
+    drop(f);
+    assert!(std::fs::read_to_string(&journal.path).is_err());
+
+    let ids: Vec<_> = journal
+        .read_entries()
+        .unwrap()
+        .into_iter()
+        .map(|e| e.id)
+        .collect();
+    assert_eq!(ids, [first.id]);
+    let second = SessionEntry::new(&cfg).unwrap();
+    journal.append(&second).unwrap();
+    assert_eq!(journal.query_range(None, None).unwrap().len(), 2);
+
+    // compaction keeps the torn bytes as they were
+    let day = journal.dir.join("journal-2024-05-01.jsonl");
+    std::fs::rename(&journal.path, &day).unwrap();
+    let report = journal
+        .compact(time::macros::date!(2024 - 05 - 02))
+        .unwrap();
+    assert_eq!(report.unparsed, 0);
+    let bytes = std::fs::read(&day).unwrap();
+    assert!(bytes.windows(cut).any(|w| w == &line[..cut]));
+}
@@ -0,0 +1,89 @@
+mod common;
+
+use common::TempDir;
+use pomodoro_cli::infra::storage::{JournalQuery, SessionEntry, SessionState};
+use time::macros::{date, datetime};
+use time::OffsetDateTime;
+
+fn entry(start: OffsetDateTime, task: &str, state: SessionState) -> SessionEntry {
+    let mut e = common::entry(start, task);
+    e.state = state;
+    e
+}
+
+#[test]
+fn query_reads_every_day_and_filters() {
+    let dir = TempDir::new("query");
+    let day = |d: &str| dir.journal(format!("journal-{}.jsonl", d));
+
+    let mut rust = entry(
+        datetime!(2024-05-01 09:00 UTC),
+        "Study Rust #learning",
+        SessionState::Ongoing,
+    );
+    day("2024-05-01").append(&rust).unwrap();
+    // later snapshot of the same session replaces the first one
+    rust.state = SessionState::Completed;
+    day("2024-05-01").append(&rust).unwrap();
+    day("2024-05-02")
+        .append(&entry(
+            datetime!(2024-05-02 14:00 UTC),
+            "Review PRs #work",
+            SessionState::Interrupted,
+        ))
+        .unwrap();
+    day("2024-05-03")
+        .append(&entry(
+            datetime!(2024-05-03 08:30 UTC),
+            "Rust book #Learning",
+            SessionState::Completed,
+        ))
+        .unwrap();
+    std::fs::write(dir.join("journal-today.md"), "not a journal").unwrap();
+
+    let journal = day("2024-05-03");
+    let all = journal.query(&JournalQuery::default()).unwrap();
+    assert_eq!(all.len(), 3);
+    assert_eq!(all[0].state, SessionState::Completed);
+    assert!(all.windows(2).all(|w| w[0].start < w[1].start));
+
+    let tasks = |q: JournalQuery| -> Vec<String> {
+        journal
+            .query(&q)
+            .unwrap()
+            .into_iter()
+            .map(|e| e.cfg.task.unwrap())
+            .collect()
+    };
+    assert_eq!(
+        tasks(JournalQuery {
+            from: Some(date!(2024 - 05 - 02)),
+            to: Some(date!(2024 - 05 - 02)),
+            ..JournalQuery::default()
+        }),
+        ["Review PRs #work"]
+    );
+    assert_eq!(
+        tasks(JournalQuery {
+            task: Some("rust".into()),
+            ..JournalQuery::default()
+        })
+        .len(),
+        2
+    );
+    assert_eq!(
+        tasks(JournalQuery {
+            tag: Some("#learning".into()),
+            from: Some(date!(2024 - 05 - 02)),
+            ..JournalQuery::default()
+        }),
+        ["Rust book #Learning"]
+    );
+    assert_eq!(
+        tasks(JournalQuery {
+            state: Some("interrupted".parse().unwrap()),
+            ..JournalQuery::default()
+        }),
+        ["Review PRs #work"]
+    );
+}
@@ -0,0 +1,84 @@
+mod common;
+
+use common::TempDir;
+use pomodoro_cli::domain::clock::{Clock, VirtualClock};
+use pomodoro_cli::domain::config::Config;
+use pomodoro_cli::infra::storage::{SessionEntry, SessionState};
+use std::sync::Arc;
+use time::macros::{date, datetime};
+use time::Duration;
+
+#[test]
+fn latest_unfinished_session_is_found_and_reopened() {
+    let clock = VirtualClock::new(datetime!(2024-05-02 10:00 UTC));
+    let dir = TempDir::new("resume");
+    let journal = dir
+        .journal("journal-test.jsonl")
+        .with_clock(Arc::new(clock.clone()));
+    assert_eq!(journal.today(), date!(2024 - 05 - 02));
+    let cfg = Config::default();
+    let schedule = cfg.clone().into_schedule();
+
+    let mut done = SessionEntry::new_with_clock(&cfg, &clock).unwrap();
+    done.start -= Duration::hours(2);
+    done.state = SessionState::Completed;
+    journal.append(&done).unwrap();
+
+    let mut e = SessionEntry::new_with_clock(&cfg, &clock).unwrap();
+    let t0 = e.start;
+    for (i, seg) in schedule.segments.iter().take(2).enumerate() {
+        let at = t0 + Duration::minutes(30 * i as i64);
+        e.start_segment(seg, at);
+        e.finish_segment(at + Duration::seconds(seg.seconds as i64));
+        journal.append(&e).unwrap();
+    }
+    // crash in the middle of the 3rd segment
+    e.start_segment(&schedule.segments[2], t0 + Duration::minutes(60));
+    e.last_updated = t0 + Duration::minutes(70);
+    journal.append(&e).unwrap();
+
+    let mut found = journal.latest_unfinished().unwrap().expect("unfinished");
+    assert_eq!(found.start, t0);
+    assert_eq!(found.completed_segments(), 2);
+
+    let back = t0 + Duration::minutes(80);
+    found.reopen(back);
+    assert_eq!(found.state, SessionState::Ongoing);
+    assert_eq!(found.segments[2].actual_secs, 600);
+    assert_eq!(found.pauses.last().unwrap().seconds, 600);
+    assert_eq!(found.completed_segments(), 2);
+
+    found.state = SessionState::Completed;
+    found.end = Some(clock.now());
+    journal.append(&found).unwrap();
+    assert!(journal.latest_unfinished().unwrap().is_none());
+}
+
+#[test]
+fn a_session_cut_off_before_midnight_is_found_the_next_day() {
+    let clock = VirtualClock::new(datetime!(2024-05-02 23:40 UTC));
+    let dir = TempDir::new("midnight");
+    let day = |d: &str| {
+        dir.journal(format!("journal-{}.jsonl", d))
+            .with_clock(Arc::new(clock.clone()))
+    };
+    let cfg = Config::default();
+
+    // unfinished, but too old to offer
+    let mut old = SessionEntry::new_with_clock(&cfg, &clock).unwrap();
+    old.start -= Duration::days(3);
+    old.last_updated = old.start;
+    day("2024-04-29").append(&old).unwrap();
+
+    let e = SessionEntry::new_with_clock(&cfg, &clock).unwrap();
+    day("2024-05-02").append(&e).unwrap();
+
+    clock.jump_wall(Duration::minutes(30));
+    let journal = day("2024-05-03");
+    assert_eq!(journal.today(), date!(2024 - 05 - 03));
+    let found = journal
+        .latest_unfinished()
+        .unwrap()
+        .expect("yesterday's session");
+    assert_eq!(found.id, e.id);
+}
@@ -0,0 +1,155 @@
+mod common;
+
+use common::{entry, TempDir};
+use pomodoro_cli::domain::clock::{Calendar, VirtualClock};
+use pomodoro_cli::domain::config::Config;
+use pomodoro_cli::domain::stats::Stats;
+use pomodoro_cli::infra::storage::{JournalQuery, SessionEntry};
+use std::sync::Arc;
+use time::macros::{date, datetime, offset};
+
+/// New York in winter, days starting at 04:00.
+fn night_owl() -> Calendar {
+    Calendar {
+        offset: offset!(-5),
+        day_start_hour: 4,
+    }
+}
+
+#[test]
+fn days_follow_offset_and_start_hour() {
+    let cal = night_owl();
+    // 01:30 local on May 3rd still belongs to May 2nd
+    assert_eq!(
+        cal.day_of(datetime!(2024-05-03 06:30 UTC)),
+        date!(2024 - 05 - 02)
+    );
+    assert_eq!(
+        cal.day_of(datetime!(2024-05-03 09:00 UTC)),
+        date!(2024 - 05 - 03)
+    );
+    assert_eq!(
+        cal.day_start(date!(2024 - 05 - 03)),
+        datetime!(2024-05-03 09:00 UTC)
+    );
+    assert_eq!(
+        cal.day_end(date!(2024 - 05 - 03)),
+        datetime!(2024-05-04 09:00 UTC)
+    );
+    assert_eq!(
+        Calendar::default().day_of(datetime!(2024-05-03 06:30 UTC)),
+        date!(2024 - 05 - 03)
+    );
+    assert!(cal
+        .describe()
+        .starts_with("Days from 04:00 in each session's own offset (UTC-05:00"));
+}
+
+#[test]
+fn queries_and_stats_use_journal_days() {
+    let dir = TempDir::new("local");
+    let journal = dir
+        .journal("journal-2024-05-03.jsonl")
+        .with_calendar(night_owl());
+    // evening, after midnight, next morning
+    for (start, task) in [
+        (datetime!(2024-05-02 23:00 UTC), "evening"),
+        (datetime!(2024-05-03 06:30 UTC), "late night"),
+        (datetime!(2024-05-03 13:00 UTC), "morning"),
+    ] {
+        journal.append(&entry(start, task)).unwrap();
+    }
+
+    let day = |d| -> Vec<String> {
+        journal
+            .query(&JournalQuery {
+                from: Some(d),
+                to: Some(d),
+                ..JournalQuery::default()
+            })
+            .unwrap()
+            .into_iter()
+            .map(|e| e.cfg.task.unwrap())
+            .collect()
+    };
+    assert_eq!(day(date!(2024 - 05 - 02)), ["evening", "late night"]);
+    assert_eq!(day(date!(2024 - 05 - 03)), ["morning"]);
+
+    let all = journal.query(&JournalQuery::default()).unwrap();
+    let stats = Stats::compute(&all, journal.calendar(), date!(2024 - 05 - 03));
+    let days: Vec<_> = stats.per_day.iter().map(|d| d.period.as_str()).collect();
+    assert_eq!(days, ["2024-05-02", "2024-05-03"]);
+}
+
+#[test]
+fn exports_show_the_local_offset() {
+    let dir = TempDir::new("offset");
+    let cal = Calendar {
+        offset: offset!(+2),
+        day_start_hour: 0,
+    };
+    // already tomorrow in local time
+    let clock = VirtualClock::new(datetime!(2024-05-02 22:30 UTC));
+    let journal = dir
+        .journal("journal-2024-05-03.jsonl")
+        .with_calendar(cal)
+        .with_clock(Arc::new(clock.clone()));
+    assert_eq!(journal.today(), date!(2024 - 05 - 03));
+    let e = SessionEntry::new_with_clock(&Config::default(), &clock).unwrap();
+    journal.append(&e).unwrap();
+
+    journal.export_csv_today().unwrap();
+    let csv = std::fs::read_to_string(dir.join("journal-today.csv")).unwrap();
+    let row = csv.lines().nth(1).expect("today's session");
+    assert!(row.contains("2024-05-03T00:30:00+02:00"), "{}", row);
+    journal.export_markdown_today().unwrap();
+    let md = std::fs::read_to_string(dir.join("journal-today.md")).unwrap();
+    assert!(md.contains("+02:00"), "{}", md);
+    assert!(md.contains("(UTC+02:00 for times in UTC)_"));
+}
+
+#[test]
+fn sessions_keep_the_offset_they_were_recorded_in() {
+    let dir = TempDir::new("dst");
+    // Paris, read after the switch to summer time
+    let journal = dir
+        .journal("journal-2024-04-01.jsonl")
+        .with_calendar(Calendar {
+            offset: offset!(+2),
+            day_start_hour: 0,
+        });
+    // 23:30 local the evenings before and after the switch, and an older
+    // line stamped in UTC (22:00 local)
+    for (start, task) in [
+        (datetime!(2024-03-30 23:30 +1), "winter"),
+        (datetime!(2024-03-31 20:00 UTC), "legacy"),
+        (datetime!(2024-03-31 23:30 +2), "summer"),
+    ] {
+        journal.append(&entry(start, task)).unwrap();
+    }
+
+    let day = |d| -> Vec<String> {
+        journal
+            .query(&JournalQuery {
+                from: Some(d),
+                to: Some(d),
+                ..JournalQuery::default()
+            })
+            .unwrap()
+            .into_iter()
+            .map(|e| e.cfg.task.unwrap())
+            .collect()
+    };
+    assert_eq!(day(date!(2024 - 03 - 30)), ["winter"]);
+    assert_eq!(day(date!(2024 - 03 - 31)), ["legacy", "summer"]);
+    assert!(day(date!(2024 - 04 - 01)).is_empty());
+
+    let all = journal.query(&JournalQuery::default()).unwrap();
+    assert_eq!(all[0].start.offset(), offset!(+1));
+    let stats = Stats::compute(&all, journal.calendar(), date!(2024 - 04 - 01));
+    let days: Vec<_> = stats.per_day.iter().map(|d| d.period.as_str()).collect();
+    assert_eq!(days, ["2024-03-30", "2024-03-31"]);
+    let cal = journal.calendar();
+    let hours: Vec<_> = all.iter().map(|e| cal.local_time(e.start).hour()).collect();
+    assert_eq!(hours, [23, 22, 23]);
+}
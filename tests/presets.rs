@@ -0,0 +1,199 @@
+mod common;
+
+use common::TempDir;
+use pomodoro_cli::domain::layers::{load_with, ConfigSource};
+use pomodoro_cli::infra::presets::PresetStore;
+use pomodoro_cli::{CliArgs, Config};
+use std::fs;
+
+/// A preset store in a fresh temp dir, removed with the returned guard.
+fn temp_store(name: &str) -> (TempDir, PresetStore) {
+    let dir = TempDir::new(name);
+    let store = PresetStore {
+        dir: dir.to_path_buf(),
+    };
+    (dir, store)
+}
+
+#[test]
+fn builtin_presets_are_valid() {
+    let (_dir, store) = temp_store("builtins");
+    let builtins: Vec<_> = store
+        .list()
+        .unwrap()
+        .into_iter()
+        .filter(|p| p.builtin)
+        .collect();
+    let names: Vec<_> = builtins.iter().map(|p| p.name.as_str()).collect();
+    assert_eq!(names, ["classic", "50-10", "ultradian"]);
+    for p in &builtins {
+        let cfg = store.load(&p.name).unwrap();
+        cfg.validate().unwrap();
+    }
+    assert_eq!(store.load("ultradian").unwrap().focus_min, 90);
+    assert!(store.delete("classic").is_err());
+    assert!(store.save("classic", &Config::default(), true).is_err());
+}
+
+#[test]
+fn saved_presets_round_trip() {
+    let (_dir, store) = temp_store("saved");
+    let cfg = Config {
+        focus_min: 40,
+        short_min: 8,
+        cycles: 2,
+        ..Config::default()
+    };
+    let path = store.save("deep-work", &cfg, false).unwrap();
+    assert!(path.ends_with("deep-work.toml"));
+    assert_eq!(store.load("deep-work").unwrap(), cfg);
+    assert!(store.save("deep-work", &cfg, false).is_err());
+    store.save("deep-work", &Config::default(), true).unwrap();
+    assert_eq!(store.load("deep-work").unwrap(), Config::default());
+
+    assert!(store.save("Deep Work", &cfg, false).is_err());
+    let invalid = Config {
+        focus_min: 1,
+        ..Config::default()
+    };
+    assert!(store.save("tiny", &invalid, false).is_err());
+    // a failed --force leaves the old preset alone
+    assert!(store.save("deep-work", &invalid, true).is_err());
+    assert_eq!(store.load("deep-work").unwrap(), Config::default());
+
+    // replacing a YAML preset leaves only the TOML one
+    fs::write(store.dir.join("yaml-one.yaml"), "focus_min: 30\n").unwrap();
+    let path = store.save("yaml-one", &cfg, true).unwrap();
+    assert!(path.ends_with("yaml-one.toml"));
+    assert!(!store.dir.join("yaml-one.yaml").exists());
+    assert_eq!(store.load("yaml-one").unwrap(), cfg);
+    store.delete("yaml-one").unwrap();
+
+    let listed = store.list().unwrap();
+    let saved = listed.iter().find(|p| p.name == "deep-work").unwrap();
+    assert!(!saved.builtin);
+    assert_eq!(saved.description, "25/5/15 min ×4");
+
+    store.delete("deep-work").unwrap();
+    assert!(store.load("deep-work").is_err());
+}
+
+#[test]
+fn saved_presets_leave_task_and_day_start_to_the_files() {
+    let (dir, store) = temp_store("saved-keys");
+    let cfg = Config {
+        focus_min: 40,
+        task: Some("Deep work".into()),
+        day_start_hour: 0,
+        ..Config::default()
+    };
+    let path = store.save("deep-work", &cfg, false).unwrap();
+    let body = fs::read_to_string(path).unwrap();
+    assert!(!body.contains("task") && !body.contains("day_start_hour"));
+
+    let user = dir.join("config.toml");
+    fs::write(&user, "day_start_hour = 4\ntask = \"Mail\"\n").unwrap();
+    let cli = CliArgs {
+        preset_name: Some("deep-work".into()),
+        ..CliArgs::default()
+    };
+    let resolved = load_with(&cli, Some(&user), &store, &dir, Vec::new()).unwrap();
+    assert_eq!(resolved.config.focus_min, 40);
+    assert_eq!(resolved.config.day_start_hour, 4);
+    assert_eq!(resolved.config.task.as_deref(), Some("Mail"));
+}
+
+#[test]
+fn preset_name_is_a_layer_below_flags() {
+    let (_dir, store) = temp_store("layer");
+    let cwd = store.dir.clone();
+    let cli = CliArgs {
+        preset_name: Some("50-10".into()),
+        cycles: Some(2),
+        ..CliArgs::default()
+    };
+    let env = vec![("POMODORO_FOCUS_MIN".to_string(), "30".to_string())];
+    let resolved = load_with(&cli, None, &store, &cwd, env).unwrap();
+    assert_eq!(resolved.config.focus_min, 50);
+    assert_eq!(
+        resolved.source_of("focus_min"),
+        ConfigSource::BuiltinPreset("50-10".into())
+    );
+    assert_eq!(resolved.config.cycles, 2);
+    assert_eq!(resolved.source_of("cycles"), ConfigSource::Cli);
+
+    let missing = CliArgs {
+        preset_name: Some("nope".into()),
+        ..CliArgs::default()
+    };
+    let err = load_with(&missing, None, &store, &cwd, Vec::new()).unwrap_err();
+    assert!(err.to_string().contains("no preset named"));
+}
+
+#[test]
+fn preset_format_is_sniffed_without_a_known_extension() {
+    let (_dir, store) = temp_store("sniff");
+    let toml_file = store.dir.join("deep.preset");
+    fs::write(&toml_file, "focus_min = 50\n[flowtime]\nratio = 4\n").unwrap();
+    let cfg = Config::from_preset_file(&toml_file).unwrap();
+    assert_eq!(cfg.focus_min, 50);
+    assert_eq!(cfg.flowtime.unwrap().ratio, 4);
+
+    let json_file = store.dir.join("short.preset");
+    fs::write(&json_file, r#" {"short_min": 10}"#).unwrap();
+    assert_eq!(Config::from_preset_file(&json_file).unwrap().short_min, 10);
+}
+
+#[test]
+fn presets_extend_files_and_builtins() {
+    let (_dir, store) = temp_store("extends");
+    fs::write(
+        store.dir.join("base.toml"),
+        "extends = \"ultradian\"\nshort_min = 15\ntask = \"base\"\n",
+    )
+    .unwrap();
+    fs::write(
+        store.dir.join("evening.json"),
+        r#"{"extends": "base", "cycles": 2}"#,
+    )
+    .unwrap();
+
+    let cfg = store.load("evening").unwrap();
+    assert_eq!(cfg.focus_min, 90);
+    assert_eq!(cfg.short_min, 15);
+    assert_eq!(cfg.cycles, 2);
+    assert_eq!(cfg.task.as_deref(), Some("base"));
+
+    let cli = CliArgs {
+        preset_name: Some("evening".into()),
+        ..CliArgs::default()
+    };
+    let resolved = load_with(&cli, None, &store, &store.dir, Vec::new()).unwrap();
+    assert_eq!(
+        resolved.source_of("focus_min"),
+        ConfigSource::BuiltinPreset("ultradian".into())
+    );
+    assert_eq!(
+        resolved.source_of("short_min"),
+        ConfigSource::Preset(store.dir.join("base.toml"))
+    );
+    assert_eq!(
+        resolved.source_of("cycles"),
+        ConfigSource::Preset(store.dir.join("evening.json"))
+    );
+}
+
+#[test]
+fn preset_inheritance_errors_name_the_chain() {
+    let (_dir, store) = temp_store("cycle");
+    fs::write(store.dir.join("a.toml"), "extends = \"b.toml\"\n").unwrap();
+    fs::write(store.dir.join("b.toml"), "extends = \"a\"\n").unwrap();
+    let err = format!("{:#}", store.load("a").unwrap_err());
+    assert!(err.contains("inheritance cycle"), "{}", err);
+    assert!(err.contains("a.toml extends"), "{}", err);
+
+    fs::write(store.dir.join("c.toml"), "extends = \"missing\"\n").unwrap();
+    let err = format!("{:#}", store.load("c").unwrap_err());
+    assert!(err.contains("extends = \"missing\""), "{}", err);
+    assert!(err.contains("c.toml"), "{}", err);
+}
@@ -0,0 +1,88 @@
+use pomodoro_cli::domain::clock::Calendar;
+use pomodoro_cli::domain::config::Config;
+use pomodoro_cli::domain::stats::focus_heatmap;
+use pomodoro_cli::infra::report::html;
+use pomodoro_cli::infra::storage::{SessionEntry, SessionState};
+use time::macros::{date, datetime, offset};
+use time::{Duration, OffsetDateTime};
+
+/// A session with one focus segment of `minutes` from `start`.
+fn focus(start: OffsetDateTime, minutes: i64, task: &str, state: SessionState) -> SessionEntry {
+    let cfg = Config {
+        task: Some(task.to_string()),
+        focus_min: minutes as u64,
+        cycles: 1,
+        ..Config::default()
+    };
+    let schedule = cfg.clone().into_schedule();
+    let mut e = SessionEntry::new(&cfg).unwrap();
+    e.start = start;
+    e.start_segment(&schedule.segments[0], start);
+    e.finish_segment(start + Duration::minutes(minutes));
+    e.state = state;
+    e.end = Some(start + Duration::minutes(minutes));
+    e.last_updated = start + Duration::minutes(minutes);
+    e
+}
+
+#[test]
+fn heatmap_splits_focus_by_local_hour() {
+    let cal = Calendar {
+        offset: offset!(+2),
+        day_start_hour: 0,
+    };
+    // Thursday 09:30-10:30 local
+    let e = focus(
+        datetime!(2024-05-02 07:30 UTC),
+        60,
+        "x",
+        SessionState::Completed,
+    );
+    let grid = focus_heatmap(&[e], &cal);
+    assert_eq!(grid[3][9], 30 * 60);
+    assert_eq!(grid[3][10], 30 * 60);
+    assert_eq!(grid.iter().flatten().sum::<u64>(), 60 * 60);
+}
+
+#[test]
+fn report_is_one_offline_file_with_every_chart() {
+    let entries = [
+        focus(
+            datetime!(2024-05-01 09:00 UTC),
+            25,
+            "Review <PRs> & \"docs\"",
+            SessionState::Completed,
+        ),
+        focus(
+            datetime!(2024-05-03 14:00 UTC),
+            50,
+            "Study Rust",
+            SessionState::Interrupted,
+        ),
+    ];
+    let out = html(
+        "2024-05-01 → 2024-05-07",
+        &entries,
+        &Calendar::default(),
+        date!(2024 - 05 - 07),
+    );
+    assert!(out.starts_with("<!DOCTYPE html>"));
+    assert!(out.contains("<title>Pomodoro report (2024-05-01 → 2024-05-07)</title>"));
+    assert_eq!(out.matches("<svg ").count(), 4);
+    assert_eq!(out.matches("<svg ").count(), out.matches("</svg>").count());
+    // three days of bars, the empty one included
+    assert!(out.contains("<title>2024-05-02: 0 min</title>"));
+    assert!(out.contains("<title>Fri 14:00: 50 min</title>"));
+    assert!(out.contains("Review &lt;PRs&gt; &amp; &quot;docs&quot;"));
+    assert!(out.contains("completed 1 (50%)"));
+    assert!(out.contains("interrupted 1 (50%)"));
+    // nothing to fetch
+    assert!(!out.contains("<script"));
+    assert!(!out.contains("href="));
+    assert!(!out.contains("src="));
+
+    let empty = html("today", &[], &Calendar::default(), date!(2024 - 05 - 07));
+    assert!(empty.contains("No sessions in this period."));
+    assert!(!empty.contains("<svg"));
+    assert!(empty.contains("<p class=\"note\">Days from 00:00 in each session&#39;s own offset"));
+}
@@ -1,4 +1,5 @@
-use pomodoro_cli::domain::schedule::Schedule;
+use pomodoro_cli::domain::schedule::{Schedule, SegmentKind, SegmentSpec};
+use pomodoro_cli::Config;
 
 #[test]
 fn schedule_length_for_2_cycles() {
@@ -6,3 +7,66 @@ fn schedule_length_for_2_cycles() {
     // 2 focuses + 1 short + 1 long = 4 segments
     assert_eq!(s.segments.len(), 4);
 }
+
+#[test]
+fn schedule_from_segment_list() {
+    let specs: Vec<SegmentSpec> = serde_json::from_str(
+        r#"[
+            {"kind": "short_break", "minutes": 5, "label": "Warm-up"},
+            {"kind": "focus", "minutes": 50},
+            {"kind": "break", "minutes": 10},
+            {"kind": "Focus", "minutes": 90, "label": "Deep work"},
+            {"kind": "long_break", "minutes": 30}
+        ]"#,
+    )
+    .unwrap();
+    let s = Schedule::from_specs(&specs);
+    s.validate().unwrap();
+    assert_eq!(s.segments.len(), 5);
+    assert_eq!(s.cycles(), Some(2));
+    assert_eq!(s.segments[0].label(), "Warm-up");
+    assert_eq!(s.segments[0].cycle_index, 1);
+    assert_eq!(s.segments[3].label(), "Deep work");
+    assert_eq!(s.segments[3].seconds, 90 * 60);
+    assert_eq!(s.segments[4].kind, SegmentKind::LongBreak);
+    assert_eq!(s.segments[4].label(), "LONG BREAK");
+    assert_eq!(s.segments[4].cycle_index, 2);
+}
+
+#[test]
+fn long_break_every_n_cycles_across_rounds() {
+    let cfg = Config {
+        cycles: 4,
+        long_every: Some(2),
+        rounds: 2,
+        ..Config::default()
+    };
+    cfg.validate().unwrap();
+    let s = Schedule::from_config(&cfg);
+    assert_eq!(s.total(), Some(16));
+    assert_eq!(s.cycles(), Some(8));
+    let longs: Vec<u8> = s
+        .segments
+        .iter()
+        .filter(|seg| seg.kind == SegmentKind::LongBreak)
+        .map(|seg| seg.cycle_index)
+        .collect();
+    assert_eq!(longs, [2, 4, 6, 8]);
+    assert_eq!(s.segments[8].cycle_index, 5);
+}
+
+#[test]
+fn endless_schedule_keeps_counting_cycles() {
+    let cfg = Config {
+        cycles: 2,
+        endless: true,
+        ..Config::default()
+    };
+    let s = Schedule::from_config(&cfg);
+    assert_eq!(s.total(), None);
+    assert_eq!(s.cycles(), None);
+    let seg = s.segment(9).unwrap();
+    assert_eq!(seg.kind, SegmentKind::ShortBreak);
+    assert_eq!(seg.cycle_index, 5);
+    assert_eq!(s.segment(11).unwrap().kind, SegmentKind::LongBreak);
+}
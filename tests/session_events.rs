@@ -0,0 +1,70 @@
+mod common;
+
+use common::TempDir;
+use pomodoro_cli::domain::clock::VirtualClock;
+use pomodoro_cli::domain::events::{next_event, SessionEvent};
+use pomodoro_cli::infra::storage::SessionState;
+use pomodoro_cli::{Config, SessionRunner};
+use std::sync::Arc;
+use time::macros::datetime;
+
+#[tokio::test]
+async fn events_drive_subscribers_and_interrupt() {
+    let dir = TempDir::new("events");
+    let path = dir.join("journal-test.jsonl");
+    let journal = dir.journal("journal-test.jsonl");
+
+    let clock = VirtualClock::new(datetime!(2024-05-02 09:00 UTC));
+    let mut runner =
+        SessionRunner::new(Config::default(), journal, false, false).with_clock(Arc::new(clock));
+    let control = runner.control();
+    let mut rx = runner.subscribe();
+
+    // stop the session as soon as the 3rd segment starts
+    let follower = tokio::spawn(async move {
+        let mut seen = Vec::new();
+        while let Some(ev) = next_event(&mut rx).await {
+            match &ev {
+                SessionEvent::SessionStarted { .. } => seen.push("started"),
+                SessionEvent::SegmentStarted { index, .. } => {
+                    seen.push("segment");
+                    if *index == 2 {
+                        control.interrupt();
+                    }
+                }
+                SessionEvent::SegmentCompleted { .. } => seen.push("completed"),
+                SessionEvent::Interrupted { .. } => seen.push("interrupted"),
+                SessionEvent::Finished { .. } => seen.push("finished"),
+                _ => {}
+            }
+            if ev.is_last() {
+                break;
+            }
+        }
+        seen
+    });
+
+    let entry = runner.run().await.unwrap();
+    let seen = follower.await.unwrap();
+
+    assert_eq!(entry.state, SessionState::Interrupted);
+    assert_eq!(entry.completed_segments(), 2);
+    assert_eq!(
+        seen,
+        [
+            "started",
+            "segment",
+            "completed",
+            "segment",
+            "completed",
+            "segment",
+            "interrupted"
+        ]
+    );
+
+    // 2 partial lines + the interrupted one, all written by the journal subscriber
+    let content = std::fs::read_to_string(&path).unwrap();
+    let last = content.lines().last().unwrap();
+    assert_eq!(content.lines().count(), 3);
+    assert!(last.contains("\"Interrupted\""));
+}
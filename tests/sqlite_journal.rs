@@ -0,0 +1,86 @@
+#![cfg(feature = "sqlite")]
+
+mod common;
+
+use common::{entry, TempDir};
+use pomodoro_cli::infra::sqlite::SqliteJournal;
+use pomodoro_cli::infra::storage::{JournalBackend, JsonlJournal, SessionState};
+use pomodoro_cli::Journal;
+use std::sync::Arc;
+use time::macros::datetime;
+
+#[test]
+fn sqlite_keeps_one_row_per_session() {
+    let dir = TempDir::new("sqlite");
+    let db = SqliteJournal::open(&dir.join("journal.db")).unwrap();
+    let mut a = entry(datetime!(2024-05-01 09:00 UTC), "a");
+    db.append(&a).unwrap();
+    a.state = SessionState::Completed;
+    db.append(&a).unwrap();
+    db.append(&entry(datetime!(2024-05-03 09:00 UTC), "b"))
+        .unwrap();
+    db.append(&entry(datetime!(2024-05-02 23:59 UTC), "c"))
+        .unwrap();
+
+    let all = db.query_range(None, None).unwrap();
+    let tasks: Vec<_> = all.iter().map(|e| e.cfg.task.as_deref().unwrap()).collect();
+    assert_eq!(tasks, ["a", "c", "b"]);
+    assert_eq!(all[0].state, SessionState::Completed);
+    assert_eq!(
+        db.get(a.id).unwrap().unwrap().state,
+        SessionState::Completed
+    );
+
+    let range = db
+        .query_range(
+            Some(datetime!(2024-05-02 00:00 UTC)),
+            Some(datetime!(2024-05-03 09:00 UTC)),
+        )
+        .unwrap();
+    assert_eq!(range.len(), 1);
+    assert!(Journal::with_backend(dir.to_path_buf(), Arc::new(db))
+        .sessions()
+        .unwrap()
+        .is_empty());
+
+    // reopening sees the same data
+    let db = SqliteJournal::open(&dir.join("journal.db")).unwrap();
+    assert_eq!(db.query_range(None, None).unwrap().len(), 3);
+}
+
+#[test]
+fn jsonl_history_imports_into_sqlite() {
+    let dir = TempDir::new("import");
+    let jsonl = JsonlJournal {
+        dir: dir.to_path_buf(),
+        path: dir.join("journal-2024-05-02.jsonl"),
+    };
+    let mut a = entry(datetime!(2024-05-01 09:00 UTC), "a");
+    a.append_to_path(&dir.join("journal-2024-05-01.jsonl"))
+        .unwrap();
+    a.state = SessionState::Interrupted;
+    a.append_to_path(&dir.join("journal-2024-05-01.jsonl"))
+        .unwrap();
+    jsonl
+        .append(&entry(datetime!(2024-05-02 10:00 UTC), "b"))
+        .unwrap();
+
+    let db = SqliteJournal::open(&dir.join("journal.db")).unwrap();
+    let journal = Journal::with_backend(dir.to_path_buf(), Arc::new(db));
+    assert_eq!(journal.import(&jsonl).unwrap(), 2);
+    assert_eq!(journal.import(&jsonl).unwrap(), 2);
+
+    let all = journal.read_all().unwrap();
+    assert_eq!(all.len(), 2);
+    assert_eq!(all[0].id, a.id);
+    assert_eq!(all[0].state, SessionState::Interrupted);
+
+    // resumed and completed in SQLite after the import
+    a.state = SessionState::Completed;
+    a.last_updated += time::Duration::hours(1);
+    journal.append(&a).unwrap();
+    // the stale JSONL snapshot does not undo it
+    assert_eq!(journal.import(&jsonl).unwrap(), 2);
+    let all = journal.read_all().unwrap();
+    assert_eq!(all[0].state, SessionState::Completed);
+}
@@ -0,0 +1,94 @@
+use pomodoro_cli::domain::clock::Calendar;
+use pomodoro_cli::domain::config::Config;
+use pomodoro_cli::domain::stats::Stats;
+use pomodoro_cli::infra::storage::{SessionEntry, SessionState};
+use time::macros::{date, datetime};
+use time::{Duration, OffsetDateTime};
+
+/// A session that ran its first `segments` segments to the end.
+fn session(start: OffsetDateTime, task: Option<&str>, segments: usize) -> SessionEntry {
+    let cfg = Config {
+        task: task.map(str::to_string),
+        ..Config::default()
+    };
+    let schedule = cfg.clone().into_schedule();
+    let mut e = SessionEntry::new(&cfg).unwrap();
+    e.start = start;
+    let mut at = start;
+    for seg in schedule.segments.iter().take(segments) {
+        e.start_segment(seg, at);
+        at += Duration::seconds(seg.seconds as i64);
+        e.finish_segment(at);
+    }
+    e.state = if segments == schedule.segments.len() {
+        SessionState::Completed
+    } else {
+        SessionState::Interrupted
+    };
+    e
+}
+
+#[test]
+fn stats_sum_focus_by_period_and_task() {
+    let entries = vec![
+        session(datetime!(2024-04-29 09:00 UTC), Some("Rust"), 8),
+        session(datetime!(2024-04-30 09:00 UTC), Some("Rust"), 3),
+        session(datetime!(2024-04-30 14:00 UTC), None, 1),
+        session(datetime!(2024-05-02 09:00 UTC), Some("Review"), 2),
+        // interrupted before any focus: no streak day
+        session(datetime!(2024-05-03 09:00 UTC), Some("Review"), 0),
+    ];
+    let stats = Stats::compute(&entries, &Calendar::default(), date!(2024 - 05 - 03));
+
+    assert_eq!(stats.sessions, 5);
+    assert_eq!(
+        (stats.completed, stats.interrupted, stats.ongoing),
+        (1, 4, 0)
+    );
+    assert_eq!(stats.completion_rate(), Some(20));
+    // 4 + 2 + 1 + 1 focus segments of 25 min
+    assert_eq!(stats.focus_secs, 8 * 25 * 60);
+
+    let days: Vec<_> = stats
+        .per_day
+        .iter()
+        .map(|d| (d.period.as_str(), d.focus_secs / 60, d.sessions))
+        .collect();
+    assert_eq!(
+        days,
+        [
+            ("2024-04-29", 100, 1),
+            ("2024-04-30", 75, 2),
+            ("2024-05-02", 25, 1),
+            ("2024-05-03", 0, 1)
+        ]
+    );
+    let weeks: Vec<_> = stats.per_week.iter().map(|w| w.period.as_str()).collect();
+    assert_eq!(weeks, ["2024-W18"]);
+    let months: Vec<_> = stats
+        .per_month
+        .iter()
+        .map(|m| (m.period.as_str(), m.focus_secs / 60))
+        .collect();
+    assert_eq!(months, [("2024-04", 175), ("2024-05", 25)]);
+
+    assert_eq!(stats.per_task[0].task.as_deref(), Some("Rust"));
+    assert_eq!(stats.per_task[0].focus_secs / 60, 150);
+    assert_eq!(stats.per_task[0].sessions, 2);
+    assert!(stats.per_task.iter().any(|t| t.task.is_none()));
+
+    assert_eq!(stats.longest_streak_days, 2);
+    // nothing yet on the 3rd, the streak of the 2nd still counts
+    assert_eq!(stats.current_streak_days, 1);
+    assert_eq!(
+        Stats::compute(&entries, &Calendar::default(), date!(2024 - 05 - 05)).current_streak_days,
+        0
+    );
+}
+
+#[test]
+fn stats_of_nothing_are_zero() {
+    let stats = Stats::compute(&[], &Calendar::default(), date!(2024 - 05 - 03));
+    assert_eq!(stats, Stats::default());
+    assert_eq!(stats.completion_rate(), None);
+}
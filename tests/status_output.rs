@@ -0,0 +1,113 @@
+mod common;
+
+use common::TempDir;
+use pomodoro_cli::domain::clock::{Clock, VirtualClock};
+use pomodoro_cli::domain::events::{next_event, SessionEvent};
+use pomodoro_cli::domain::schedule::SegmentKind;
+use pomodoro_cli::domain::status::{RunState, SessionStatus, DEFAULT_STATUS_FORMAT};
+use pomodoro_cli::infra::status_file::StatusFile;
+use pomodoro_cli::{Config, SessionRunner};
+use std::sync::Arc;
+use time::macros::datetime;
+
+#[test]
+fn render_and_waybar() {
+    let status = SessionStatus {
+        state: RunState::Running,
+        kind: Some(SegmentKind::Focus),
+        remaining_secs: 754,
+        planned_secs: 1500,
+        segment_index: 2,
+        segments: 8,
+        cycle_index: 2,
+        cycles: 4,
+        task: Some("Study Rust".into()),
+        ..SessionStatus::default()
+    };
+    assert_eq!(
+        status.render(DEFAULT_STATUS_FORMAT),
+        "FOCUS 12:34 – Study Rust"
+    );
+    assert_eq!(
+        status.render("{kind} {remaining} ({cycle}/{cycles}) {percent}%"),
+        "FOCUS 12:34 (2/4) 49%"
+    );
+
+    let no_task = SessionStatus {
+        task: None,
+        ..status.clone()
+    };
+    assert_eq!(no_task.render(DEFAULT_STATUS_FORMAT), "FOCUS 12:34");
+
+    let bar = serde_json::to_value(status.waybar(DEFAULT_STATUS_FORMAT)).unwrap();
+    assert_eq!(bar["text"], "FOCUS 12:34 – Study Rust");
+    assert_eq!(bar["class"], "focus");
+    assert_eq!(bar["percentage"], 49);
+
+    let idle = SessionStatus::default().waybar(DEFAULT_STATUS_FORMAT);
+    assert_eq!(idle.text, "");
+    assert_eq!(idle.class, "idle");
+}
+
+#[tokio::test]
+async fn status_file_follows_session() {
+    let dir = TempDir::new("status");
+    let journal = dir.journal("journal-test.jsonl");
+    let status_file = StatusFile {
+        path: dir.join("status.json"),
+    };
+
+    let start = datetime!(2024-05-02 09:00 UTC);
+    let clock = VirtualClock::new(start);
+    let cfg = Config {
+        task: Some("Study Rust".into()),
+        ..Config::default()
+    };
+    let mut runner = SessionRunner::new(cfg, journal, false, false)
+        .with_clock(Arc::new(clock.clone()))
+        .with_status_file(status_file.clone());
+    let control = runner.control();
+    let mut rx = runner.subscribe();
+
+    // stop 60s into the second cycle's focus
+    let follower = tokio::spawn(async move {
+        while let Some(ev) = next_event(&mut rx).await {
+            if let SessionEvent::Tick {
+                index: 2,
+                remaining_secs,
+                planned_secs,
+                ..
+            } = ev
+            {
+                if planned_secs - remaining_secs == 60 {
+                    control.interrupt();
+                }
+            }
+            if ev.is_last() {
+                break;
+            }
+        }
+    });
+    runner.run().await.unwrap();
+    follower.await.unwrap();
+
+    let status = status_file.read(clock.now()).unwrap();
+    assert_eq!(status.state, RunState::Interrupted);
+    assert_eq!(status.kind, Some(SegmentKind::Focus));
+    assert_eq!(status.cycle_index, 2);
+    assert_eq!(status.cycles, 4);
+    // the engine may tick once more before it sees the interrupt
+    assert!((25 * 60 - 61..=25 * 60 - 60).contains(&status.remaining_secs));
+    assert_eq!(status.task.as_deref(), Some("Study Rust"));
+
+    // an active status nobody refreshes any more reads as idle
+    let active = SessionStatus {
+        state: RunState::Running,
+        updated_at: Some(start),
+        ..status
+    };
+    status_file.write(&active).unwrap();
+    assert!(status_file.read(start).unwrap().is_active());
+    let later = start + time::Duration::minutes(1);
+    assert_eq!(status_file.read(later).unwrap().state, RunState::Idle);
+}
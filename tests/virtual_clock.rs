@@ -0,0 +1,199 @@
+mod common;
+
+use common::TempDir;
+use pomodoro_cli::domain::clock::VirtualClock;
+use pomodoro_cli::domain::schedule::SegmentKind;
+use pomodoro_cli::infra::storage::SessionState;
+use pomodoro_cli::{Config, SessionRunner};
+use std::sync::Arc;
+use time::macros::datetime;
+use time::Duration;
+
+#[tokio::test]
+async fn full_session_runs_on_virtual_clock() {
+    let dir = TempDir::new("vclock");
+    let path = dir.join("journal-test.jsonl");
+    let journal = dir.journal("journal-test.jsonl");
+
+    let t0 = datetime!(2024-05-02 09:00 UTC);
+    let clock = VirtualClock::new(t0);
+    let mut runner =
+        SessionRunner::new(Config::default(), journal, false, false).with_clock(Arc::new(clock));
+
+    let started = std::time::Instant::now();
+    let entry = runner.run().await.unwrap();
+    assert!(started.elapsed() < std::time::Duration::from_secs(10));
+
+    // 4 × 25 focus + 3 × 5 short + 15 long
+    assert_eq!(entry.state, SessionState::Completed);
+    assert_eq!(entry.start, t0);
+    assert_eq!(entry.end, Some(t0 + Duration::minutes(130)));
+    assert_eq!(entry.segments.len(), 8);
+    assert_eq!(entry.segments[1].kind, SegmentKind::ShortBreak);
+    assert_eq!(entry.segments[1].start, Some(t0 + Duration::minutes(25)));
+    assert_eq!(entry.segments[7].kind, SegmentKind::LongBreak);
+    assert_eq!(entry.segments[7].start, Some(t0 + Duration::minutes(115)));
+    assert_eq!(entry.focus_secs(), 100 * 60);
+
+    // 8 partial lines + the final one
+    let lines = std::fs::read_to_string(&path).unwrap();
+    assert_eq!(lines.lines().count(), 9);
+}
+
+/// Run a default session, calling `on_first_segment` when the first focus starts.
+async fn run_with(
+    name: &str,
+    on_first_segment: impl FnOnce(&VirtualClock) + Send + 'static,
+) -> pomodoro_cli::infra::storage::SessionEntry {
+    use pomodoro_cli::domain::events::{next_event, SessionEvent};
+
+    let dir = TempDir::new(name);
+    let journal = dir.journal("journal-test.jsonl");
+
+    let clock = VirtualClock::new(datetime!(2024-05-02 09:00 UTC));
+    let mut runner = SessionRunner::new(Config::default(), journal, false, false)
+        .with_clock(Arc::new(clock.clone()));
+    let mut rx = runner.subscribe();
+    let follower = tokio::spawn(async move {
+        let mut hook = Some(on_first_segment);
+        while let Some(ev) = next_event(&mut rx).await {
+            if let SessionEvent::SegmentStarted { .. } = ev {
+                if let Some(f) = hook.take() {
+                    f(&clock);
+                }
+            }
+            if ev.is_last() {
+                break;
+            }
+        }
+    });
+    let entry = runner.run().await.unwrap();
+    follower.await.unwrap();
+    entry
+}
+
+#[tokio::test]
+async fn suspend_is_credited_and_recorded() {
+    let t0 = datetime!(2024-05-02 09:00 UTC);
+    let entry = run_with("suspend", |c| {
+        c.suspend(std::time::Duration::from_secs(600))
+    })
+    .await;
+
+    // the 25 minute focus still ends 25 minutes (wall) after it started
+    assert_eq!(entry.segments[1].start, Some(t0 + Duration::minutes(25)));
+    assert_eq!(entry.segments[0].actual_secs, 1500);
+    assert_eq!(entry.clock_gaps.len(), 1);
+    assert_eq!(entry.clock_gaps[0].seconds, 600);
+    assert_eq!(entry.end, Some(t0 + Duration::minutes(130)));
+}
+
+#[tokio::test]
+async fn backward_clock_jump_does_not_stretch_segment() {
+    let t0 = datetime!(2024-05-02 09:00 UTC);
+    let entry = run_with("jump", |c| c.jump_wall(Duration::hours(-1))).await;
+
+    assert_eq!(entry.clock_gaps.len(), 1);
+    assert_eq!(entry.clock_gaps[0].seconds, -3600);
+    // still 25 minutes of running time, timestamps follow the (moved) wall clock
+    assert_eq!(entry.segments[0].actual_secs, 1500);
+    assert_eq!(
+        entry.segments[1].start,
+        Some(t0 + Duration::minutes(25) - Duration::hours(1))
+    );
+}
+
+#[tokio::test]
+async fn endless_session_runs_until_stopped() {
+    use pomodoro_cli::domain::events::{next_event, SessionEvent};
+
+    let dir = TempDir::new("endless");
+    let journal = dir.journal("journal-test.jsonl");
+
+    let cfg = Config {
+        cycles: 2,
+        endless: true,
+        ..Config::default()
+    };
+    let clock = VirtualClock::new(datetime!(2024-05-02 09:00 UTC));
+    let mut runner =
+        SessionRunner::new(cfg, journal, false, false).with_clock(Arc::new(clock.clone()));
+    let control = runner.control();
+    let mut rx = runner.subscribe();
+    // stop when the 4th round starts
+    tokio::spawn(async move {
+        while let Some(ev) = next_event(&mut rx).await {
+            if let SessionEvent::SegmentStarted { index, total, .. } = ev {
+                assert_eq!(total, None);
+                if index == 12 {
+                    control.interrupt();
+                    break;
+                }
+            }
+        }
+    });
+
+    let entry = runner.run().await.unwrap();
+    assert_eq!(entry.state, SessionState::Interrupted);
+    assert_eq!(entry.completed_segments(), 12);
+    assert_eq!(entry.segments[12].cycle_index, 7);
+    assert_eq!(entry.segments[11].kind, SegmentKind::LongBreak);
+    assert_eq!(entry.segments[11].cycle_index, 6);
+}
+
+#[tokio::test]
+async fn flowtime_break_follows_focus_length() {
+    use pomodoro_cli::domain::config::Flowtime;
+    use pomodoro_cli::domain::events::{next_event, SessionEvent};
+
+    let dir = TempDir::new("flow");
+    let journal = dir.journal("journal-test.jsonl");
+
+    let cfg = Config {
+        flowtime: Some(Flowtime::default()),
+        ..Config::default()
+    };
+    cfg.validate().unwrap();
+    let clock = VirtualClock::new(datetime!(2024-05-02 09:00 UTC));
+    let mut runner =
+        SessionRunner::new(cfg, journal, false, false).with_clock(Arc::new(clock.clone()));
+    let control = runner.control();
+    let mut rx = runner.subscribe();
+    // end the first flow after 50 min, stop during the second one after 20 min
+    tokio::spawn(async move {
+        while let Some(ev) = next_event(&mut rx).await {
+            if let SessionEvent::Tick {
+                index,
+                elapsed_secs,
+                remaining_secs,
+                ..
+            } = ev
+            {
+                match (index, elapsed_secs) {
+                    (0, 3000) => {
+                        assert_eq!(remaining_secs, 0);
+                        control.skip();
+                    }
+                    (2, 1200) => {
+                        control.interrupt();
+                        break;
+                    }
+                    _ => {}
+                }
+            }
+        }
+    });
+
+    let entry = runner.run().await.unwrap();
+    assert_eq!(entry.state, SessionState::Interrupted);
+    assert_eq!(entry.segments[0].kind, SegmentKind::Flow);
+    assert!(entry.segments[0].is_complete());
+    assert!(!entry.segments[0].skipped);
+    assert!((3000..=3001).contains(&entry.segments[0].actual_secs));
+    assert_eq!(entry.segments[1].kind, SegmentKind::ShortBreak);
+    assert_eq!(entry.segments[1].planned_secs, 600);
+    assert_eq!(entry.segments[1].actual_secs, 600);
+    assert_eq!(entry.segments[2].cycle_index, 2);
+    assert!((1200..=1201).contains(&entry.segments[2].actual_secs));
+    assert!((4200..=4202).contains(&entry.focus_secs()));
+}